@@ -0,0 +1,21 @@
+//! A language server for POSIX-compliant dotenv files, communicating over stdio.
+
+use std::error::Error;
+
+use lsp_server::Connection;
+use lsp_types::InitializeParams;
+
+use server::Server;
+
+mod project;
+mod server;
+
+fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let (connection, io_threads) = Connection::stdio();
+    let capabilities = serde_json::to_value(server::capabilities())?;
+    let params: InitializeParams = serde_json::from_value(connection.initialize(capabilities)?)?;
+    Server::new(params).run(&connection)?;
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
@@ -0,0 +1,473 @@
+//! The dotenv files of a workspace, and the variables they define.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::PathBuf,
+};
+
+use lsp_types::{
+    CompletionItem, CompletionItemKind, Diagnostic, DiagnosticSeverity, Location, NumberOrString,
+    Position, Range, TextEdit, Url,
+};
+use potenv::{
+    env::EnvProvider,
+    evaluator::{resolver::Resolvers, Evaluator},
+    lint::{Level, Linter},
+    options::Options,
+    parser::{
+        ast::{Assignment, Expression},
+        parse_with_options, ParseError,
+    },
+};
+
+#[cfg(test)]
+mod tests;
+
+/// A variable name, either assigned or referenced in a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    pub range: Range,
+    /// Whether the symbol is the name of an assignment, rather than a reference.
+    pub definition: bool,
+    /// The index of the assignment containing the symbol.
+    pub assignment: usize,
+}
+
+#[derive(Debug)]
+struct Document {
+    uri: Url,
+    text: String,
+    /// The assignments of the last successful parse.
+    ast: Vec<Assignment>,
+    error: Option<ParseError>,
+    symbols: Vec<Symbol>,
+    /// Whether the document is part of the project even when it is not open in the editor.
+    configured: bool,
+}
+
+impl Document {
+    fn new(uri: Url, text: String, configured: bool, options: Options) -> Self {
+        let mut document = Self {
+            uri,
+            text: String::new(),
+            ast: Vec::new(),
+            error: None,
+            symbols: Vec::new(),
+            configured,
+        };
+        document.update(text, options);
+        document
+    }
+
+    fn update(&mut self, text: String, options: Options) {
+        match parse_with_options(&text, Some(file_path(&self.uri)), options) {
+            Ok(ast) => {
+                self.symbols = symbols(&text, &ast);
+                self.ast = ast;
+                self.error = None;
+            }
+            Err(err) => self.error = Some(err),
+        }
+        self.text = text;
+    }
+
+    fn symbol_at(&self, position: Position) -> Option<&Symbol> {
+        self.symbols
+            .iter()
+            .find(|s| s.range.start <= position && position <= s.range.end)
+    }
+}
+
+/// The dotenv files of a workspace, evaluated in order.
+///
+/// Configured files are always part of the project, other documents only while they are open.
+#[derive(Debug)]
+pub struct Project<E: EnvProvider> {
+    documents: Vec<Document>,
+    env: E,
+    options: Options,
+}
+
+impl<E: EnvProvider> Project<E> {
+    pub fn new(env: E, options: Options) -> Self {
+        Self {
+            documents: Vec::new(),
+            env,
+            options,
+        }
+    }
+
+    /// Adds a file that is part of the project, reading it from disk.
+    pub fn add_file(&mut self, uri: Url) -> std::io::Result<()> {
+        let text = std::fs::read_to_string(file_path(&uri))?;
+        self.documents
+            .push(Document::new(uri, text, true, self.options));
+        Ok(())
+    }
+
+    /// Updates the contents of a document, adding it to the project if needed.
+    pub fn open(&mut self, uri: Url, text: String) {
+        let options = self.options;
+        match self.document_mut(&uri) {
+            Some(document) => document.update(text, options),
+            None => {
+                let document = Document::new(uri, text, false, options);
+                self.documents.push(document);
+            }
+        }
+    }
+
+    /// Reverts a configured document to its contents on disk, or removes it from the project.
+    pub fn close(&mut self, uri: &Url) {
+        let Some(index) = self.documents.iter().position(|d| &d.uri == uri) else {
+            return;
+        };
+        let options = self.options;
+        let document = &mut self.documents[index];
+        match document.configured {
+            true => match std::fs::read_to_string(file_path(uri)) {
+                Ok(text) => document.update(text, options),
+                Err(_) => {
+                    self.documents.remove(index);
+                }
+            },
+            false => {
+                self.documents.remove(index);
+            }
+        }
+    }
+
+    /// Returns the URIs of the documents, in evaluation order.
+    pub fn documents(&self) -> impl Iterator<Item = &Url> {
+        self.documents.iter().map(|d| &d.uri)
+    }
+
+    pub fn symbol_at(&self, uri: &Url, position: Position) -> Option<&Symbol> {
+        self.document(uri)?.symbol_at(position)
+    }
+
+    /// Returns the assignment that a variable at the given position refers to.
+    pub fn definition(&self, uri: &Url, position: Position) -> Option<Location> {
+        let symbol = self.symbol_at(uri, position)?;
+        if symbol.definition {
+            return Some(Location::new(uri.clone(), symbol.range));
+        }
+        let index = self.documents.iter().position(|d| &d.uri == uri)?;
+        self.documents[..=index]
+            .iter()
+            .flat_map(|d| d.symbols.iter().map(move |s| (d, s)))
+            .rfind(|(d, s)| {
+                s.definition
+                    && s.name == symbol.name
+                    && (&d.uri != uri || s.assignment < symbol.assignment)
+            })
+            .map(|(d, s)| Location::new(d.uri.clone(), s.range))
+    }
+
+    /// Returns the edits renaming the variable at the given position, across all documents.
+    pub fn rename(
+        &self,
+        uri: &Url,
+        position: Position,
+        new_name: &str,
+    ) -> Option<HashMap<Url, Vec<TextEdit>>> {
+        let name = &self.symbol_at(uri, position)?.name;
+        let mut changes = HashMap::new();
+        for document in &self.documents {
+            let edits: Vec<_> = document
+                .symbols
+                .iter()
+                .filter(|s| &s.name == name)
+                .map(|s| TextEdit::new(s.range, new_name.to_owned()))
+                .collect();
+            if !edits.is_empty() {
+                changes.insert(document.uri.clone(), edits);
+            }
+        }
+        Some(changes)
+    }
+
+    /// Describes the resolved value of the variable at the given position, and where it comes from.
+    /// `${namespace:argument}` expansions are kept as written, with their argument evaluated,
+    /// since only applications register resolvers.
+    pub fn hover(&self, uri: &Url, position: Position) -> Option<String> {
+        let name = &self.symbol_at(uri, position)?.name;
+        let mut evaluator =
+            Evaluator::new(&self.env, false).resolvers(self.placeholder_resolvers());
+        for document in &self.documents {
+            if let Err(err) = evaluator.evaluate(document.ast.clone()) {
+                return Some(format!("`{name}` cannot be resolved: {err}"));
+            }
+        }
+        // Variables of the environment take precedence over the dotenv files.
+        let (value, provenance) = match (self.env.var(name), evaluator.into_scope().get(name)) {
+            (Some(value), _) => (value, "Set in the environment".to_owned()),
+            (None, Some(value)) => {
+                let definition = self
+                    .documents
+                    .iter()
+                    .flat_map(|d| d.symbols.iter().map(move |s| (d, s)))
+                    .rfind(|(_, s)| s.definition && &s.name == name);
+                let provenance = match definition {
+                    Some((document, symbol)) => format!(
+                        "Defined in `{}` at line {}",
+                        file_name(&document.uri),
+                        symbol.range.start.line + 1
+                    ),
+                    None => "Assigned by an expansion".to_owned(),
+                };
+                (value.clone(), provenance)
+            }
+            (None, None) => return Some(format!("`{name}` is not defined")),
+        };
+        Some(format!("`{name}={value}`\n\n{provenance}"))
+    }
+
+    /// Returns resolvers expanding to their own expansion, for every namespace of the documents.
+    fn placeholder_resolvers(&self) -> Resolvers {
+        let mut namespaces = BTreeSet::new();
+        for node in self.documents.iter().flat_map(|d| &d.ast) {
+            collect_namespaces(&node.value, &mut namespaces);
+        }
+        let mut resolvers = Resolvers::default();
+        for namespace in namespaces {
+            let prefix = format!("${{{namespace}:");
+            resolvers.register(namespace, move |argument| {
+                Ok(format!("{prefix}{argument}}}"))
+            });
+        }
+        resolvers
+    }
+
+    /// Returns the names of the variables defined by the documents, followed by the given ones.
+    pub fn completions<I>(&self, env: I) -> Vec<CompletionItem>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut items: Vec<CompletionItem> = Vec::new();
+        for document in &self.documents {
+            let detail = file_name(&document.uri);
+            for symbol in document.symbols.iter().filter(|s| s.definition) {
+                if items.iter().all(|item| item.label != symbol.name) {
+                    items.push(completion(&symbol.name, &detail));
+                }
+            }
+        }
+        for name in env {
+            if items.iter().all(|item| item.label != name) {
+                items.push(completion(&name, "environment"));
+            }
+        }
+        items
+    }
+
+    /// Returns the syntax errors and lint diagnostics of every document.
+    pub fn diagnostics(&self, linter: &Linter) -> Vec<(Url, Vec<Diagnostic>)> {
+        let mut diagnostics: Vec<_> = self
+            .documents
+            .iter()
+            .map(|d| {
+                let diagnostics = d.error.iter().map(|err| syntax_error(&d.text, err));
+                (d.uri.clone(), diagnostics.collect::<Vec<_>>())
+            })
+            .collect();
+        let files = self
+            .documents
+            .iter()
+            .map(|d| (Some(file_path(&d.uri)), d.ast.clone()));
+        let paths: Vec<_> = self.documents.iter().map(|d| file_path(&d.uri)).collect();
+        for diagnostic in linter.lint(files) {
+            let Some(index) = paths
+                .iter()
+                .position(|p| Some(p) == diagnostic.file.as_ref())
+            else {
+                continue;
+            };
+            let text = &self.documents[index].text;
+            let position = diagnostic.position;
+            let start = lsp_position(text, position.line, position.column);
+            let severity = match diagnostic.level {
+                Level::Error => DiagnosticSeverity::ERROR,
+                _ => DiagnosticSeverity::WARNING,
+            };
+            diagnostics[index].1.push(Diagnostic {
+                range: Range::new(start, word_end(text, start)),
+                severity: Some(severity),
+                code: Some(NumberOrString::String(diagnostic.rule.code().to_owned())),
+                source: Some("potenv".to_owned()),
+                message: diagnostic.message,
+                ..Default::default()
+            });
+        }
+        diagnostics
+    }
+
+    fn document(&self, uri: &Url) -> Option<&Document> {
+        self.documents.iter().find(|d| &d.uri == uri)
+    }
+
+    fn document_mut(&mut self, uri: &Url) -> Option<&mut Document> {
+        self.documents.iter_mut().find(|d| &d.uri == uri)
+    }
+}
+
+fn file_path(uri: &Url) -> PathBuf {
+    uri.to_file_path()
+        .unwrap_or_else(|_| PathBuf::from(uri.path()))
+}
+
+fn file_name(uri: &Url) -> String {
+    uri.path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .unwrap_or_else(|| uri.as_str())
+        .to_owned()
+}
+
+fn completion(name: &str, detail: &str) -> CompletionItem {
+    CompletionItem {
+        label: name.to_owned(),
+        kind: Some(CompletionItemKind::VARIABLE),
+        detail: Some(detail.to_owned()),
+        ..Default::default()
+    }
+}
+
+fn syntax_error(text: &str, err: &ParseError) -> Diagnostic {
+    let start = match err {
+        ParseError::Syntax(err) => lsp_position(text, err.line(), err.column()),
+        ParseError::Unexpected(token) => {
+            lsp_position(text, token.position.line, token.position.column)
+        }
+        ParseError::Eof | ParseError::UnknownOperator(_) | ParseError::UnsupportedDialect(_) => {
+            Position::default()
+        }
+    };
+    let end = Position::new(start.line, start.character + 1);
+    Diagnostic {
+        range: Range::new(start, end),
+        severity: Some(DiagnosticSeverity::ERROR),
+        source: Some("potenv".to_owned()),
+        message: err.to_string(),
+        ..Default::default()
+    }
+}
+
+/// Collects the variable names of the assignments, along with their ranges in the text.
+fn symbols(text: &str, ast: &[Assignment]) -> Vec<Symbol> {
+    let mut symbols = Vec::new();
+    for (index, node) in ast.iter().enumerate() {
+        let position = node.position;
+        if let Some(symbol) = symbol(text, &node.name, position.line, position.column) {
+            symbols.push(Symbol {
+                definition: true,
+                assignment: index,
+                ..symbol
+            });
+        }
+        collect_references(text, &node.value, index, &mut symbols);
+    }
+    symbols
+}
+
+fn collect_references(text: &str, value: &[Expression], index: usize, out: &mut Vec<Symbol>) {
+    for expr in value {
+        let (name, position, values) = match expr {
+            Expression::Characters(_) | Expression::Tilde(_) => continue,
+            Expression::Expansion(node) => (&node.name, node.position, [&node.rhs[..], &[]]),
+            Expression::Length(node) => (&node.name, node.position, [&[][..], &[]]),
+            Expression::Substring(node) => (&node.name, node.position, [&[][..], &[]]),
+            Expression::Filtered(node) => (&node.name, node.position, [&[][..], &[]]),
+            Expression::Resolved(node) => {
+                collect_references(text, &node.argument, index, out);
+                continue;
+            }
+            Expression::Quoted(node) => {
+                collect_references(text, std::slice::from_ref(node), index, out);
+                continue;
+            }
+            Expression::Replacement(node) => (
+                &node.name,
+                node.position,
+                [&node.pattern[..], &node.replacement[..]],
+            ),
+        };
+        if let Some(symbol) = symbol(text, name, position.line, position.column) {
+            out.push(Symbol {
+                assignment: index,
+                ..symbol
+            });
+        }
+        for value in values {
+            collect_references(text, value, index, out);
+        }
+    }
+}
+
+fn collect_namespaces(value: &[Expression], out: &mut BTreeSet<String>) {
+    for expr in value {
+        let values = match expr {
+            Expression::Expansion(node) => [&node.rhs[..], &[]],
+            Expression::Replacement(node) => [&node.pattern[..], &node.replacement[..]],
+            Expression::Resolved(node) => {
+                out.insert(node.namespace.clone());
+                [&node.argument[..], &[]]
+            }
+            Expression::Quoted(node) => [std::slice::from_ref(&**node), &[]],
+            Expression::Characters(_)
+            | Expression::Tilde(_)
+            | Expression::Length(_)
+            | Expression::Substring(_)
+            | Expression::Filtered(_) => continue,
+        };
+        for value in values {
+            collect_namespaces(value, out);
+        }
+    }
+}
+
+/// Returns a reference symbol for a name at the given 1-based position,
+/// or `None` if the parser did not record its position.
+fn symbol(text: &str, name: &str, line: usize, column: usize) -> Option<Symbol> {
+    if line == 0 {
+        return None;
+    }
+    let start = lsp_position(text, line, column);
+    let length: usize = name.chars().map(char::len_utf16).sum();
+    let end = Position::new(start.line, start.character + length as u32);
+    Some(Symbol {
+        name: name.to_owned(),
+        range: Range::new(start, end),
+        definition: false,
+        assignment: 0,
+    })
+}
+
+/// Converts a 1-based position counted in characters
+/// to a 0-based position counted in UTF-16 code units.
+fn lsp_position(text: &str, line: usize, column: usize) -> Position {
+    let line_text = text.split('\n').nth(line.saturating_sub(1)).unwrap_or("");
+    let character: usize = line_text
+        .chars()
+        .take(column.saturating_sub(1))
+        .map(char::len_utf16)
+        .sum();
+    Position::new(line.saturating_sub(1) as u32, character as u32)
+}
+
+/// Returns the end of the identifier starting at the given position.
+fn word_end(text: &str, start: Position) -> Position {
+    let line_text = text.split('\n').nth(start.line as usize).unwrap_or("");
+    let mut character = 0;
+    let mut length = 0;
+    for ch in line_text.chars() {
+        if character >= start.character {
+            if !(ch == '_' || ch.is_ascii_alphanumeric()) {
+                break;
+            }
+            length += 1;
+        }
+        character += ch.len_utf16() as u32;
+    }
+    Position::new(start.line, start.character + length)
+}
@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+
+use lsp_types::{Location, Position, Range, Url};
+use potenv::{lint::Linter, options::Options};
+use rstest::rstest;
+
+use super::Project;
+
+fn uri(name: &str) -> Url {
+    Url::parse(&format!("file:///project/{name}")).unwrap()
+}
+
+fn range(line: u32, start: u32, end: u32) -> Range {
+    Range::new(Position::new(line, start), Position::new(line, end))
+}
+
+fn project(files: &[(&str, &str)]) -> Project<HashMap<String, String>> {
+    let env = HashMap::from([("HOME".to_owned(), "/home/user".to_owned())]);
+    let mut project = Project::new(env, Options::default());
+    for (name, text) in files {
+        project.open(uri(name), text.to_string());
+    }
+    project
+}
+
+#[rstest]
+#[case::reference(".env", Position::new(1, 3), Some((".env", range(0, 0, 1))))]
+#[case::braced_reference(".env", Position::new(1, 6), Some((".env", range(0, 0, 1))))]
+#[case::definition(".env", Position::new(0, 0), Some((".env", range(0, 0, 1))))]
+#[case::other_file(".env.local", Position::new(0, 4), Some((".env", range(1, 0, 1))))]
+#[case::self_reference(".env.local", Position::new(1, 5), Some((".env.local", range(0, 0, 1))))]
+#[case::external(".env", Position::new(2, 7), None)]
+#[case::value(".env", Position::new(2, 3), None)]
+fn test_definition(
+    #[case] file: &str,
+    #[case] position: Position,
+    #[case] expected: Option<(&str, Range)>,
+) {
+    let project = project(&[
+        (".env", "A=1\nB=$A${A:-x}\nC=\"é ${HOME}\""),
+        (".env.local", "A=${B}\nA=${A}2"),
+    ]);
+    let expected = expected.map(|(name, range)| Location::new(uri(name), range));
+    assert_eq!(expected, project.definition(&uri(file), position));
+}
+
+#[test]
+fn test_utf16_positions() {
+    let project = project(&[(".env", "A='😀' B=1 C=$B")]);
+    let symbol = project
+        .symbol_at(&uri(".env"), Position::new(0, 14))
+        .unwrap();
+    assert_eq!(
+        ("B", range(0, 14, 15)),
+        (symbol.name.as_str(), symbol.range)
+    );
+}
+
+#[test]
+fn test_rename() {
+    let project = project(&[(".env", "A=1\nB=${A:-$A}"), (".env.local", "A=$A")]);
+    let changes = project
+        .rename(&uri(".env.local"), Position::new(0, 3), "RENAMED")
+        .unwrap();
+    let ranges = |name| {
+        let edits = &changes[&uri(name)];
+        assert!(edits.iter().all(|e| e.new_text == "RENAMED"));
+        edits.iter().map(|e| e.range).collect::<Vec<_>>()
+    };
+    assert_eq!(
+        vec![range(0, 0, 1), range(1, 4, 5), range(1, 8, 9)],
+        ranges(".env")
+    );
+    assert_eq!(vec![range(0, 0, 1), range(0, 3, 4)], ranges(".env.local"));
+}
+
+#[rstest]
+#[case::defined(
+    Position::new(1, 0),
+    "`B=1/home/user`\n\nDefined in `.env.local` at line 1"
+)]
+#[case::environment(Position::new(1, 6), "`HOME=/home/user`\n\nSet in the environment")]
+#[case::undefined(Position::new(2, 4), "`C` is not defined")]
+#[case::assigned_by_expansion(Position::new(3, 4), "`D=1`\n\nAssigned by an expansion")]
+fn test_hover(#[case] position: Position, #[case] expected: &str) {
+    let project = project(&[
+        (".env", "A=1\nB=$A${HOME}\nX=${C:-}\nY=${D:=1}"),
+        (".env.local", "B=1$HOME"),
+    ]);
+    let hover = project.hover(&uri(".env"), position);
+    assert_eq!(Some(expected), hover.as_deref());
+}
+
+#[test]
+fn test_hover_with_resolvers() {
+    let options = Options {
+        resolvers: true,
+        ..Options::default()
+    };
+    let mut project = Project::new(HashMap::new(), options);
+    project.open(
+        uri(".env"),
+        "A=db\nB=${vault:secret/${A:-x}}\nC=\"${B}!\"".to_owned(),
+    );
+    let hover = project.hover(&uri(".env"), Position::new(2, 0));
+    assert_eq!(
+        Some("`C=${vault:secret/db}!`\n\nDefined in `.env` at line 3"),
+        hover.as_deref()
+    );
+}
+
+#[test]
+fn test_hover_with_evaluation_error() {
+    let project = project(&[(".env", "A=${B:?}")]);
+    let hover = project.hover(&uri(".env"), Position::new(0, 4)).unwrap();
+    assert!(hover.starts_with("`B` cannot be resolved: "), "{hover}");
+}
+
+#[test]
+fn test_completions() {
+    let project = project(&[(".env", "A=1\nB=$C"), (".env.local", "A=2\nD=")]);
+    let items: Vec<_> = project
+        .completions(["HOME".to_owned(), "D".to_owned()])
+        .into_iter()
+        .map(|item| (item.label, item.detail.unwrap()))
+        .collect();
+    let expected = [
+        ("A", ".env"),
+        ("B", ".env"),
+        ("D", ".env.local"),
+        ("HOME", "environment"),
+    ];
+    assert_eq!(
+        expected.map(|(l, d)| (l.to_owned(), d.to_owned())).to_vec(),
+        items
+    );
+}
+
+#[test]
+fn test_diagnostics() {
+    let project = project(&[
+        (".env", "abc=1"),
+        (".env.local", "abc=2"),
+        (".env.test", "A=1\nB='unterminated"),
+    ]);
+    let diagnostics: Vec<_> = project
+        .diagnostics(&Linter::new())
+        .into_iter()
+        .map(|(uri, diagnostics)| {
+            let ranges: Vec<_> = diagnostics.into_iter().map(|d| d.range).collect();
+            (uri, ranges)
+        })
+        .collect();
+    assert_eq!(
+        vec![
+            (uri(".env"), vec![range(0, 0, 3)]),
+            (uri(".env.local"), vec![range(0, 0, 3), range(0, 0, 3)]),
+            (uri(".env.test"), vec![range(1, 2, 3)]),
+        ],
+        diagnostics
+    );
+}
+
+#[test]
+fn test_close() {
+    let mut project = project(&[(".env", "A=1")]);
+    project.close(&uri(".env"));
+    assert_eq!(0, project.documents().count());
+}
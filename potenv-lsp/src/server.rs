@@ -0,0 +1,272 @@
+use std::error::Error;
+
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+        Notification as LspNotification, PublishDiagnostics,
+    },
+    request::{Completion, GotoDefinition, HoverRequest, Rename, Request as LspRequest},
+    CompletionOptions, CompletionParams, CompletionResponse, DidChangeTextDocumentParams,
+    DidCloseTextDocumentParams, DidOpenTextDocumentParams, GotoDefinitionParams,
+    GotoDefinitionResponse, Hover, HoverContents, HoverParams, HoverProviderCapability,
+    InitializeParams, MarkupContent, MarkupKind, OneOf, PublishDiagnosticsParams, RenameParams,
+    ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind, Url, WorkspaceEdit,
+};
+use potenv::{env::ProcessEnvProvider, lint::Linter, options::Options};
+use serde::Deserialize;
+
+use crate::project::Project;
+
+/// The files evaluated when the client does not configure them.
+const DEFAULT_FILES: [&str; 2] = [".env", ".env.local"];
+
+/// Options sent by the client in the `initialize` request.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct InitializationOptions {
+    /// The dotenv files of the workspace, relative to its root, in evaluation order.
+    files: Option<Vec<String>>,
+    /// Accept a leading `export` keyword before assignments.
+    export_prefix: bool,
+    /// Accept `${#NAME}` expansions.
+    string_length: bool,
+    /// Accept `${NAME#pattern}` and `${NAME%pattern}` expansions.
+    pattern_removal: bool,
+    /// Accept the Bash `${NAME:offset:length}` and `${NAME/pattern/replacement}` expansions.
+    bash_expansions: bool,
+    /// Accept `${NAME|filter}` expansions.
+    filters: bool,
+    /// Accept `${namespace:argument}` expansions.
+    resolvers: bool,
+    /// Expand `~` and `~user` at the start of values and after `:`.
+    tilde_expansion: bool,
+}
+
+pub fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        rename_provider: Some(OneOf::Left(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec!["$".to_owned(), "{".to_owned()]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+pub struct Server {
+    project: Project<ProcessEnvProvider>,
+    linter: Linter,
+}
+
+impl Server {
+    pub fn new(params: InitializeParams) -> Self {
+        let config: InitializationOptions = params
+            .initialization_options
+            .and_then(|options| serde_json::from_value(options).ok())
+            .unwrap_or_default();
+        let options = Options {
+            export_prefix: config.export_prefix,
+            string_length: config.string_length,
+            pattern_removal: config.pattern_removal,
+            bash_expansions: config.bash_expansions,
+            filters: config.filters,
+            resolvers: config.resolvers,
+            tilde_expansion: config.tilde_expansion,
+            ..Default::default()
+        };
+        let mut project = Project::new(ProcessEnvProvider, options);
+        #[allow(deprecated)]
+        let root = params
+            .workspace_folders
+            .and_then(|folders| folders.into_iter().next())
+            .map(|folder| folder.uri)
+            .or(params.root_uri);
+        if let Some(root) = root.and_then(|uri| uri.to_file_path().ok()) {
+            let configured = config.files.is_some();
+            let files = config
+                .files
+                .unwrap_or_else(|| DEFAULT_FILES.map(str::to_owned).to_vec());
+            for file in files {
+                let Ok(uri) = Url::from_file_path(root.join(&file)) else {
+                    continue;
+                };
+                // Default files are optional, configured ones are expected to exist.
+                if let Err(err) = project.add_file(uri) {
+                    if configured {
+                        eprintln!("Could not read {file}: {err}");
+                    }
+                }
+            }
+        }
+        Self {
+            project,
+            linter: Linter::new(),
+        }
+    }
+
+    pub fn run(&mut self, connection: &Connection) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.publish_diagnostics(connection, Vec::new())?;
+        for message in &connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    let response = self.handle_request(request);
+                    connection.sender.send(Message::Response(response))?;
+                }
+                Message::Notification(notification) => {
+                    let closed = self.handle_notification(notification);
+                    self.publish_diagnostics(connection, closed)?;
+                }
+                Message::Response(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&self, request: Request) -> Response {
+        match request.method.as_str() {
+            HoverRequest::METHOD => self.dispatch::<HoverRequest>(request, Self::hover),
+            GotoDefinition::METHOD => self.dispatch::<GotoDefinition>(request, Self::definition),
+            Rename::METHOD => self.dispatch::<Rename>(request, Self::rename),
+            Completion::METHOD => self.dispatch::<Completion>(request, Self::completion),
+            method => Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("Unsupported method '{method}'"),
+            ),
+        }
+    }
+
+    fn dispatch<R>(
+        &self,
+        request: Request,
+        handler: fn(&Self, R::Params) -> Result<R::Result, String>,
+    ) -> Response
+    where
+        R: LspRequest,
+    {
+        let result = serde_json::from_value(request.params)
+            .map_err(|err| err.to_string())
+            .and_then(|params| handler(self, params));
+        match result {
+            Ok(result) => Response::new_ok(request.id, result),
+            Err(message) => Response::new_err(request.id, ErrorCode::InvalidParams as i32, message),
+        }
+    }
+
+    /// Applies a notification to the project, returning the documents that left it.
+    fn handle_notification(&mut self, notification: Notification) -> Vec<Url> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                if let Ok(params) =
+                    notification.extract::<DidOpenTextDocumentParams>(DidOpenTextDocument::METHOD)
+                {
+                    let document = params.text_document;
+                    self.project.open(document.uri, document.text);
+                }
+            }
+            DidChangeTextDocument::METHOD => {
+                if let Ok(params) = notification
+                    .extract::<DidChangeTextDocumentParams>(DidChangeTextDocument::METHOD)
+                {
+                    // With full synchronization, the last change holds the whole document.
+                    if let Some(change) = params.content_changes.into_iter().last() {
+                        self.project.open(params.text_document.uri, change.text);
+                    }
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                if let Ok(params) =
+                    notification.extract::<DidCloseTextDocumentParams>(DidCloseTextDocument::METHOD)
+                {
+                    let uri = params.text_document.uri;
+                    self.project.close(&uri);
+                    if self.project.documents().all(|d| d != &uri) {
+                        return vec![uri];
+                    }
+                }
+            }
+            _ => {}
+        }
+        Vec::new()
+    }
+
+    fn publish_diagnostics(
+        &self,
+        connection: &Connection,
+        closed: Vec<Url>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let cleared = closed.into_iter().map(|uri| (uri, Vec::new()));
+        for (uri, diagnostics) in self
+            .project
+            .diagnostics(&self.linter)
+            .into_iter()
+            .chain(cleared)
+        {
+            let params = PublishDiagnosticsParams::new(uri, diagnostics, None);
+            let notification = Notification::new(PublishDiagnostics::METHOD.to_owned(), params);
+            connection
+                .sender
+                .send(Message::Notification(notification))?;
+        }
+        Ok(())
+    }
+
+    fn hover(&self, params: HoverParams) -> Result<Option<Hover>, String> {
+        let position = params.text_document_position_params;
+        let hover = self
+            .project
+            .hover(&position.text_document.uri, position.position)
+            .map(|value| Hover {
+                contents: HoverContents::Markup(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value,
+                }),
+                range: None,
+            });
+        Ok(hover)
+    }
+
+    fn definition(
+        &self,
+        params: GotoDefinitionParams,
+    ) -> Result<Option<GotoDefinitionResponse>, String> {
+        let position = params.text_document_position_params;
+        let location = self
+            .project
+            .definition(&position.text_document.uri, position.position);
+        Ok(location.map(GotoDefinitionResponse::Scalar))
+    }
+
+    fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>, String> {
+        let name = params.new_name;
+        let mut chars = name.chars();
+        let valid = chars
+            .next()
+            .is_some_and(|ch| ch == '_' || ch.is_ascii_alphabetic())
+            && chars.all(|ch| ch == '_' || ch.is_ascii_alphanumeric());
+        if !valid {
+            return Err(format!("'{name}' is not a valid variable name"));
+        }
+        let position = params.text_document_position;
+        let changes = self
+            .project
+            .rename(&position.text_document.uri, position.position, &name);
+        Ok(changes.map(WorkspaceEdit::new))
+    }
+
+    fn completion(&self, _: CompletionParams) -> Result<Option<CompletionResponse>, String> {
+        let mut env: Vec<_> = std::env::vars_os()
+            .filter_map(|(name, _)| name.into_string().ok())
+            .collect();
+        env.sort();
+        let items = self.project.completions(env);
+        Ok(Some(CompletionResponse::Array(items)))
+    }
+}
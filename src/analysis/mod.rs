@@ -0,0 +1,497 @@
+//! Detects constructs that potenv interprets differently than other dotenv implementations,
+//! to help migrating existing dotenv files.
+//!
+//! ```
+//! use potenv::analysis::{analyze, rewrite, Construct};
+//!
+//! let input = "export DEBUG=1\nGREETING=\"hello\\nworld\"\n";
+//! let findings = analyze(input);
+//! assert_eq!(Construct::ExportPrefix, findings[0].construct);
+//! assert_eq!(Construct::NewlineEscape, findings[1].construct);
+//! assert_eq!((2, 16), (findings[1].position.line, findings[1].position.column));
+//! assert_eq!("DEBUG=1\nGREETING=\"hello\nworld\"\n", rewrite(input, &findings));
+//! ```
+
+use std::ops::Range;
+
+use crate::{
+    dialect::{compose, Dialect},
+    tokenizer::{
+        err::{ErrorKind, SyntaxError},
+        is_blank, is_identifier,
+        pos::Position,
+        State, Tokenizer, TraceEntry,
+    },
+};
+
+#[cfg(test)]
+mod tests;
+
+const ALL_DIALECTS: [Dialect; 4] = [
+    Dialect::PythonDotenv,
+    Dialect::Node,
+    Dialect::Ruby,
+    Dialect::DockerCompose,
+];
+
+/// A construct that is not portable across dotenv implementations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Construct {
+    /// An `export` prefix, which potenv only accepts when enabled in the options.
+    ExportPrefix,
+    /// Whitespace around the `=` of an assignment.
+    SpaceAroundEquals,
+    /// A `:` separating a name from its value.
+    ColonSeparator,
+    /// A name that is not a valid shell identifier, like `app.name` or `app-name`.
+    InvalidName,
+    /// Whitespace followed by more characters in an unquoted value, which ends the value for potenv.
+    UnquotedWhitespace,
+    /// A shell special character (one of `|&;<>()`) in an unquoted value.
+    UnquotedSpecialCharacter,
+    /// A backslash in an unquoted value, which potenv removes.
+    UnquotedBackslash,
+    /// A `#` in an unquoted value, which some implementations treat as the start of a comment.
+    UnquotedHash,
+    /// A `\n` in a double-quoted value, which potenv does not decode.
+    NewlineEscape,
+    /// A `\'` in a single-quoted value, which potenv does not treat as an escaped quote.
+    EscapedSingleQuote,
+    /// A `${NAME}` expansion in a single-quoted value.
+    SingleQuotedExpansion,
+    /// A command substitution, like `$(cmd)` or `` `cmd` ``.
+    CommandSubstitution,
+    /// Any other syntax rejected by potenv.
+    Unsupported(ErrorKind),
+}
+
+impl Construct {
+    fn dialects(&self) -> Vec<Dialect> {
+        match self {
+            Self::ColonSeparator => vec![Dialect::Node, Dialect::Ruby, Dialect::DockerCompose],
+            Self::UnquotedBackslash => {
+                vec![Dialect::PythonDotenv, Dialect::Node, Dialect::DockerCompose]
+            }
+            Self::UnquotedHash => vec![Dialect::Node, Dialect::Ruby],
+            Self::EscapedSingleQuote | Self::SingleQuotedExpansion => vec![Dialect::PythonDotenv],
+            Self::Unsupported(_) => vec![],
+            _ => ALL_DIALECTS.to_vec(),
+        }
+    }
+}
+
+impl std::fmt::Display for Construct {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ExportPrefix => f.write_str("Export prefix"),
+            Self::SpaceAroundEquals => f.write_str("Whitespace around '='"),
+            Self::ColonSeparator => f.write_str("Colon separator"),
+            Self::InvalidName => f.write_str("Invalid variable name"),
+            Self::UnquotedWhitespace => f.write_str("Unquoted whitespace in value"),
+            Self::UnquotedSpecialCharacter => f.write_str("Unquoted special shell character"),
+            Self::UnquotedBackslash => f.write_str("Unquoted backslash"),
+            Self::UnquotedHash => f.write_str("Unquoted '#' in value"),
+            Self::NewlineEscape => f.write_str("Newline escape sequence in double quotes"),
+            Self::EscapedSingleQuote => f.write_str("Escaped quote in single quotes"),
+            Self::SingleQuotedExpansion => f.write_str("Expansion in single quotes"),
+            Self::CommandSubstitution => f.write_str("Command substitution"),
+            Self::Unsupported(kind) => kind.fmt(f),
+        }
+    }
+}
+
+/// A non-portable construct found in a dotenv file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub construct: Construct,
+    /// The position of the construct in the input.
+    pub position: Position,
+    /// The byte range of the construct in the input.
+    pub span: Range<usize>,
+    /// The dialects that interpret the construct differently than potenv.
+    pub dialects: Vec<Dialect>,
+    /// The text to replace the construct with, for potenv to interpret it like these dialects do.
+    pub rewrite: Option<String>,
+}
+
+impl Finding {
+    /// Returns the text of the construct in the analyzed input.
+    pub fn source<'a>(&self, input: &'a str) -> &'a str {
+        &input[self.span.clone()]
+    }
+}
+
+/// A construct found in the text being analyzed.
+struct Event {
+    construct: Construct,
+    span: Range<usize>,
+    dialects: Vec<Dialect>,
+    rewrite: Option<String>,
+    /// The range to blank out for the tokenizer to carry on, for constructs without a rewrite.
+    skip: Option<Range<usize>>,
+}
+
+impl Event {
+    fn new(construct: Construct, span: Range<usize>, rewrite: Option<String>) -> Self {
+        Self {
+            dialects: construct.dialects(),
+            construct,
+            span,
+            rewrite,
+            skip: None,
+        }
+    }
+
+    fn skip(mut self, range: Range<usize>) -> Self {
+        self.skip = Some(range);
+        self
+    }
+}
+
+/// Lists the non-portable constructs of a dotenv file, in order of appearance.
+///
+/// The input is tokenized following the POSIX specification.
+/// Each construct found is rewritten before tokenizing again,
+/// so that the constructs following it are found as well.
+pub fn analyze(input: &str) -> Vec<Finding> {
+    let mut text = input.to_owned();
+    let mut findings = Vec::new();
+    // The offset in `text` up to which constructs have been reported.
+    let mut done = 0;
+    // The difference between offsets in `text` and in `input`, after `done`.
+    let mut shift = 0isize;
+    loop {
+        let mut tokenizer = Tokenizer::new(text.chars(), None).traced();
+        let error = tokenizer.by_ref().find_map(Result::err);
+        let trace = tokenizer.take_trace();
+        let event = find_construct(&text, &trace, done)
+            .or_else(|| error.map(|err| classify_error(&text, &trace, err)));
+        let Some(mut event) = event else {
+            break;
+        };
+        if event.span.start < done {
+            event.rewrite = None;
+        } else {
+            let start = event.span.start.wrapping_add_signed(-shift);
+            let end = event.span.end.wrapping_add_signed(-shift);
+            findings.push(Finding {
+                construct: event.construct,
+                position: Position::at_offset(input, start),
+                span: start..end,
+                dialects: event.dialects,
+                rewrite: event.rewrite.clone(),
+            });
+        }
+        match (event.rewrite, event.skip) {
+            (Some(rewrite), _) => {
+                shift += rewrite.len() as isize - event.span.len() as isize;
+                done = event.span.start + rewrite.len();
+                text.replace_range(event.span, &rewrite);
+            }
+            (None, Some(skip)) if skip.end > done => {
+                done = skip.end;
+                blank_out(&mut text, skip);
+            }
+            (None, None) if event.span.end > done => done = event.span.end,
+            _ => break,
+        }
+    }
+    findings
+}
+
+/// Applies the rewrites of the given findings to their input.
+pub fn rewrite(input: &str, findings: &[Finding]) -> String {
+    let mut result = String::with_capacity(input.len());
+    let mut pos = 0;
+    for finding in findings {
+        if let Some(rewrite) = finding
+            .rewrite
+            .as_ref()
+            .filter(|_| finding.span.start >= pos)
+        {
+            result.push_str(&input[pos..finding.span.start]);
+            result.push_str(rewrite);
+            pos = finding.span.end;
+        }
+    }
+    result.push_str(&input[pos..]);
+    result
+}
+
+/// Replaces a range of the text with spaces, keeping newlines and byte offsets.
+fn blank_out(text: &mut String, range: Range<usize>) {
+    let blank: String = text[range.clone()]
+        .chars()
+        .map(|ch| match ch {
+            '\n' => "\n".to_owned(),
+            _ => " ".repeat(ch.len_utf8()),
+        })
+        .collect();
+    text.replace_range(range, &blank);
+}
+
+fn skip_blanks(text: &str, offset: usize) -> usize {
+    text[offset..]
+        .find(|ch| !is_blank(ch))
+        .map_or(text.len(), |i| offset + i)
+}
+
+/// Finds the first construct that tokenizes without error, starting at the `done` offset.
+fn find_construct(text: &str, trace: &[TraceEntry], done: usize) -> Option<Event> {
+    let start = trace.partition_point(|entry| entry.offset < done);
+    (start..trace.len()).find_map(|i| {
+        match_construct(text, trace, i, done).filter(|event| event.span.start >= done)
+    })
+}
+
+fn match_construct(text: &str, trace: &[TraceEntry], i: usize, done: usize) -> Option<Event> {
+    let TraceEntry { offset, ch, state } = trace[i];
+    let next = trace.get(i + 1);
+    match (state, ch) {
+        (State::AssignmentValue, '#') => Some(Event::new(
+            Construct::UnquotedHash,
+            offset..offset + 1,
+            Some(" #".to_owned()),
+        )),
+        (State::AssignmentValue, ch) if is_blank(ch) => {
+            let end = skip_blanks(text, offset);
+            if end == text.len() || text[end..].starts_with(['\n', '#']) {
+                return None;
+            }
+            // Quotes before `done` come from previous rewrites.
+            let value = trace[..i]
+                .iter()
+                .rev()
+                .take_while(|entry| entry.state != State::AssignmentName && entry.offset >= done);
+            if trace[i - 1].state == State::AssignmentName {
+                Some(Event::new(
+                    Construct::SpaceAroundEquals,
+                    offset..end,
+                    Some(String::new()),
+                ))
+            } else if value.clone().any(|entry| matches!(entry.ch, '\'' | '"')) {
+                // Implementations disagree on what follows a quoted string.
+                let line_end = text[end..].find('\n').map_or(text.len(), |i| end + i);
+                let mut event = Event::new(Construct::UnquotedWhitespace, offset..end, None);
+                event.dialects.clear();
+                Some(event.skip(offset..line_end))
+            } else {
+                Some(Event::new(
+                    Construct::UnquotedWhitespace,
+                    offset..end,
+                    Some(format!("'{}'", &text[offset..end])),
+                ))
+            }
+        }
+        (State::AssignmentValueEscape, _) => Some(Event::new(
+            Construct::UnquotedBackslash,
+            offset - 1..offset,
+            Some("\\\\".to_owned()),
+        )),
+        (State::DoubleQuotedEscape, 'n') => Some(Event::new(
+            Construct::NewlineEscape,
+            offset - 1..offset + 1,
+            Some("\n".to_owned()),
+        )),
+        (State::SingleQuoted, '\\') => {
+            let quote = next.filter(|entry| entry.ch == '\'')?;
+            let line = text[quote.offset + 1..].split('\n').next()?;
+            line.contains('\'').then(|| {
+                Event::new(
+                    Construct::EscapedSingleQuote,
+                    offset..quote.offset + 1,
+                    Some("'\\''".to_owned()),
+                )
+            })
+        }
+        (State::SingleQuoted, '$') => {
+            next.filter(|entry| entry.ch == '{' && entry.state == State::SingleQuoted)?;
+            let open = trace[..i]
+                .iter()
+                .rfind(|entry| entry.state != State::SingleQuoted)?;
+            let close = trace[i..]
+                .iter()
+                .find(|entry| entry.state == State::SingleQuoted && entry.ch == '\'')?;
+            Some(Event::new(
+                Construct::SingleQuotedExpansion,
+                open.offset..close.offset + 1,
+                Some(double_quote(&text[open.offset + 1..close.offset])),
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Double-quotes a string, keeping only its `${` expansions.
+fn double_quote(value: &str) -> String {
+    let mut result = String::with_capacity(value.len() + 2);
+    result.push('"');
+    let mut chars = value.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '$' if chars.peek() == Some(&'{') => (),
+            '"' | '\\' | '`' | '$' => result.push('\\'),
+            _ => (),
+        }
+        result.push(ch);
+    }
+    result.push('"');
+    result
+}
+
+/// Classifies a syntax error, which stopped the tokenizer at the last entry of the trace.
+fn classify_error(text: &str, trace: &[TraceEntry], err: SyntaxError) -> Event {
+    let (offset, state) = trace.last().map_or((0, State::AssignmentList), |entry| {
+        (entry.offset, entry.state)
+    });
+    let statement = trace
+        .iter()
+        .rfind(|entry| {
+            entry.state == State::AssignmentList && !matches!(entry.ch, ' ' | '\t' | '\n' | '#')
+        })
+        .map_or(0, |entry| entry.offset);
+    let line_end = text[offset..].find('\n').map_or(text.len(), |i| offset + i);
+    let kind = err.kind();
+    let event = match (&kind, state) {
+        (ErrorKind::InvalidCharacter(ch), State::AssignmentName) if is_blank(*ch) => {
+            let after = skip_blanks(text, offset);
+            if &text[statement..offset] == "export" {
+                Some(Event::new(
+                    Construct::ExportPrefix,
+                    statement..after,
+                    Some(String::new()),
+                ))
+            } else if text[after..].starts_with('=') {
+                Some(Event::new(
+                    Construct::SpaceAroundEquals,
+                    offset..skip_blanks(text, after + 1),
+                    Some("=".to_owned()),
+                ))
+            } else {
+                None
+            }
+        }
+        (ErrorKind::InvalidCharacter(':'), State::AssignmentName)
+            if text[offset + 1..].starts_with(is_blank) =>
+        {
+            Some(Event::new(
+                Construct::ColonSeparator,
+                offset..skip_blanks(text, offset + 1),
+                Some("=".to_owned()),
+            ))
+        }
+        (ErrorKind::InvalidCharacter(_), State::AssignmentList | State::AssignmentName) => {
+            invalid_name(text, statement)
+        }
+        (ErrorKind::UnescapedSpecialCharacter(ch), _) => Some(Event::new(
+            Construct::UnquotedSpecialCharacter,
+            offset..offset + ch.len_utf8(),
+            Some(format!("'{ch}'")),
+        )),
+        (ErrorKind::UnsupportedCommandExpansion, _) => {
+            let end = text[offset + 1..]
+                .find('`')
+                .map_or(line_end, |i| offset + i + 2);
+            Some(Event::new(
+                Construct::CommandSubstitution,
+                offset..end,
+                None,
+            ))
+        }
+        (ErrorKind::UnsupportedCommandOrArithmeticExpansion, _) => {
+            let end = closing_parenthesis(text, offset).unwrap_or(line_end);
+            Some(Event::new(
+                Construct::CommandSubstitution,
+                offset - 1..end,
+                None,
+            ))
+        }
+        _ => None,
+    };
+    let event = event.unwrap_or_else(|| {
+        let start = Position::new(err.line(), err.column()).offset(text);
+        let end = text[start..]
+            .chars()
+            .next()
+            .map_or(start, |ch| start + ch.len_utf8());
+        Event::new(Construct::Unsupported(kind), start..end, None)
+    });
+    match event.rewrite {
+        Some(_) => event,
+        None => {
+            let end = line_end.max(event.span.end);
+            event.skip(statement..end)
+        }
+    }
+}
+
+/// Returns the offset following the parenthesis that closes the one at `offset`.
+fn closing_parenthesis(text: &str, offset: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, ch) in text[offset..].char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' if depth == 1 => return Some(offset + i + 1),
+            ')' => depth -= 1,
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Matches an assignment to a name that potenv rejects but other dialects accept.
+fn invalid_name(text: &str, statement: usize) -> Option<Event> {
+    let end = text[statement..]
+        .find(|ch: char| matches!(ch, '=' | ':') || ch.is_whitespace())
+        .map_or(text.len(), |i| statement + i);
+    let name = &text[statement..end];
+    let separator = &text[skip_blanks(text, end)..];
+    if name.is_empty()
+        || is_identifier(name)
+        || !(separator.starts_with('=')
+            || separator.starts_with(": ")
+            || separator.starts_with(":\t"))
+    {
+        return None;
+    }
+    let dialects = name_dialects(name);
+    if dialects.is_empty() {
+        return None;
+    }
+    let mut rewrite: String = name
+        .chars()
+        .map(|ch| match ch.is_ascii_alphanumeric() {
+            true => ch,
+            false => '_',
+        })
+        .collect();
+    if rewrite.starts_with(|ch: char| ch.is_ascii_digit()) {
+        rewrite.insert(0, '_');
+    }
+    let mut event = Event::new(Construct::InvalidName, statement..end, Some(rewrite));
+    event.dialects = dialects;
+    Some(event)
+}
+
+/// Returns the dialects accepting the given variable name.
+fn name_dialects(name: &str) -> Vec<Dialect> {
+    let all = |accepts: fn(char) -> bool| name.chars().all(accepts);
+    [
+        (
+            Dialect::PythonDotenv,
+            !name.starts_with('\'') && all(|ch| !matches!(ch, '=' | '#') && !ch.is_whitespace()),
+        ),
+        (
+            Dialect::Node,
+            all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '_' | '.' | '-')),
+        ),
+        (
+            Dialect::Ruby,
+            all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '_' | '.')),
+        ),
+        (Dialect::DockerCompose, all(compose::is_key_char)),
+    ]
+    .into_iter()
+    .filter_map(|(dialect, accepted)| accepted.then_some(dialect))
+    .collect()
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use rstest::rstest;
+
+use super::{analyze, rewrite, Construct};
+use crate::{
+    dialect::Dialect,
+    evaluator::{Evaluator, Scope},
+    options::Options,
+    parser::parse_with_options,
+    tokenizer::err::ErrorKind,
+};
+
+fn evaluate(input: &str, dialect: Dialect) -> Scope {
+    let options = Options {
+        dialect,
+        ..Default::default()
+    };
+    let ast = parse_with_options(input, None, options).unwrap();
+    let env: HashMap<String, String> = HashMap::new();
+    let mut evaluator = Evaluator::new(&env, false);
+    evaluator.evaluate(ast).unwrap();
+    evaluator.into_scope()
+}
+
+#[rstest]
+#[case::export("export A=1", Construct::ExportPrefix, (1, 1), "export ", Some(""))]
+#[case::space_before_equals("A =1", Construct::SpaceAroundEquals, (1, 2), " =", Some("="))]
+#[case::space_around_equals("A = 1", Construct::SpaceAroundEquals, (1, 2), " = ", Some("="))]
+#[case::space_after_equals("A= 1", Construct::SpaceAroundEquals, (1, 3), " ", Some(""))]
+#[case::colon("A: 1", Construct::ColonSeparator, (1, 2), ": ", Some("="))]
+#[case::dotted_name("a.b=1", Construct::InvalidName, (1, 1), "a.b", Some("a_b"))]
+#[case::digit_name("1a=1", Construct::InvalidName, (1, 1), "1a", Some("_1a"))]
+#[case::unquoted_space("A=a b", Construct::UnquotedWhitespace, (1, 4), " ", Some("' '"))]
+#[case::special_char("A=a|b", Construct::UnquotedSpecialCharacter, (1, 4), "|", Some("'|'"))]
+#[case::backslash("A=a\\b", Construct::UnquotedBackslash, (1, 4), "\\", Some("\\\\"))]
+#[case::hash("A=a#b", Construct::UnquotedHash, (1, 4), "#", Some(" #"))]
+#[case::newline_escape("A=\"a\\nb\"", Construct::NewlineEscape, (1, 5), "\\n", Some("\n"))]
+#[case::escaped_single_quote("A='a\\'b'", Construct::EscapedSingleQuote, (1, 5), "\\'", Some("'\\''"))]
+#[case::single_quoted_expansion("A='$B ${C}'", Construct::SingleQuotedExpansion, (1, 3), "'$B ${C}'", Some("\"\\$B ${C}\""))]
+#[case::command("A=$(echo (a)) b", Construct::CommandSubstitution, (1, 3), "$(echo (a))", None)]
+#[case::backquotes("A=\"`echo a`\"", Construct::CommandSubstitution, (1, 4), "`echo a`", None)]
+#[case::unsupported("A=$1", Construct::Unsupported(ErrorKind::UnsupportedShellParameter("$1".into())), (1, 4), "1", None)]
+fn test_analyze(
+    #[case] input: &str,
+    #[case] construct: Construct,
+    #[case] position: (usize, usize),
+    #[case] source: &str,
+    #[case] expected: Option<&str>,
+) {
+    let findings = analyze(input);
+    assert_eq!(1, findings.len(), "{findings:?}");
+    let finding = &findings[0];
+    assert_eq!(construct, finding.construct);
+    assert_eq!(position, (finding.position.line, finding.position.column));
+    assert_eq!(source, finding.source(input));
+    assert_eq!(expected, finding.rewrite.as_deref());
+}
+
+#[test]
+fn test_analyze_portable() {
+    assert!(analyze("A=1\nB='b c'\nexport_C=\"${A} # \\\\\" # comment\n").is_empty());
+}
+
+#[rstest]
+#[case::dashed("a-b=1", vec![Dialect::PythonDotenv, Dialect::Node, Dialect::DockerCompose])]
+#[case::non_ascii("CAFÉ=1", vec![Dialect::PythonDotenv])]
+fn test_analyze_name_dialects(#[case] input: &str, #[case] dialects: Vec<Dialect>) {
+    let findings = analyze(input);
+    assert_eq!(Construct::InvalidName, findings[0].construct);
+    assert_eq!(dialects, findings[0].dialects);
+}
+
+#[test]
+fn test_analyze_whitespace_after_quotes() {
+    let findings = analyze("A='a' b c\nB=1");
+    assert_eq!(1, findings.len());
+    assert_eq!(Construct::UnquotedWhitespace, findings[0].construct);
+    assert_eq!(None, findings[0].rewrite);
+    assert!(findings[0].dialects.is_empty());
+}
+
+#[test]
+fn test_analyze_skips_unsupported_statements() {
+    let input = "A=$(cmd\nB=1\nC=a b\nD='unterminated";
+    let constructs: Vec<_> = analyze(input)
+        .into_iter()
+        .map(|f| (f.construct, f.position.line))
+        .collect();
+    assert_eq!(
+        vec![
+            (Construct::CommandSubstitution, 1),
+            (Construct::UnquotedWhitespace, 3),
+            (
+                Construct::Unsupported(ErrorKind::UnterminatedSingleQuotedString),
+                4
+            ),
+        ],
+        constructs
+    );
+}
+
+#[rstest]
+#[case::export_with_spaces("export A = a b c # comment\n")]
+#[case::several_per_line("A=a b\nb-c: \"d\\ne\"#g\n")]
+#[case::multiline("A=\"a\nb\\nc\"\nB=a\\b\n")]
+fn test_rewrite_preserves_dialects(#[case] input: &str) {
+    let findings = analyze(input);
+    let rewritten = rewrite(input, &findings);
+    // Invalid names are renamed, so only values are compared.
+    let expected: Vec<_> = evaluate(&rewritten, Dialect::Posix).into_values().collect();
+    for dialect in [
+        Dialect::PythonDotenv,
+        Dialect::Node,
+        Dialect::Ruby,
+        Dialect::DockerCompose,
+    ] {
+        if findings.iter().all(|f| f.dialects.contains(&dialect)) {
+            let values: Vec<_> = evaluate(input, dialect).into_values().collect();
+            assert_eq!(expected, values, "{dialect:?}");
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use clap::{Args, ValueEnum};
+use potenv::export::{
+    docker::DockerEnvFile,
+    github::GithubActions,
+    json::Json,
+    kubernetes::{ConfigMap, Secret},
+    pam::EtcEnvironment,
+    shell::Shell,
+    systemd::EnvironmentFile,
+    toml::Toml,
+    yaml::Yaml,
+    Exporter,
+};
+
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    /// The output format.
+    #[arg(long, value_enum, default_value_t = Format::Posix)]
+    format: Format,
+    /// Nest variables by splitting their names on a separator (JSON, YAML and TOML only).
+    #[arg(long, value_name = "SEPARATOR")]
+    nest: Option<String>,
+    /// The name of the Kubernetes resource.
+    #[arg(long, required_if_eq_any = [("format", "configmap"), ("format", "secret")])]
+    name: Option<String>,
+    /// The namespace of the Kubernetes resource.
+    #[arg(long)]
+    namespace: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Format {
+    Posix,
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+    Nushell,
+    Json,
+    Yaml,
+    Toml,
+    Docker,
+    Configmap,
+    Secret,
+    Systemd,
+    EtcEnvironment,
+    Github,
+}
+
+impl ExportArgs {
+    pub fn exporter(self) -> Box<dyn Exporter> {
+        match self.format {
+            Format::Posix => Box::new(Shell::Posix),
+            Format::Bash => Box::new(Shell::Bash),
+            Format::Zsh => Box::new(Shell::Zsh),
+            Format::Fish => Box::new(Shell::Fish),
+            Format::Powershell => Box::new(Shell::PowerShell),
+            Format::Nushell => Box::new(Shell::Nushell),
+            Format::Json => Box::new(match self.nest {
+                Some(sep) => Json::new().nested(sep),
+                None => Json::new(),
+            }),
+            Format::Yaml => Box::new(match self.nest {
+                Some(sep) => Yaml::new().nested(sep),
+                None => Yaml::new(),
+            }),
+            Format::Toml => Box::new(match self.nest {
+                Some(sep) => Toml::new().nested(sep),
+                None => Toml::new(),
+            }),
+            Format::Docker => Box::new(DockerEnvFile),
+            Format::Systemd => Box::new(EnvironmentFile),
+            Format::EtcEnvironment => Box::new(EtcEnvironment),
+            Format::Github => Box::new(GithubActions),
+            Format::Configmap => {
+                let resource = ConfigMap::new(self.name.unwrap_or_default());
+                Box::new(match self.namespace {
+                    Some(ns) => resource.namespace(ns),
+                    None => resource,
+                })
+            }
+            Format::Secret => {
+                let resource = Secret::new(self.name.unwrap_or_default());
+                Box::new(match self.namespace {
+                    Some(ns) => resource.namespace(ns),
+                    None => resource,
+                })
+            }
+        }
+    }
+}
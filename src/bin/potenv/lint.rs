@@ -0,0 +1,52 @@
+use clap::{Args, ValueEnum};
+use potenv::lint::{
+    report::{json, sarif},
+    Diagnostic, Level, Linter, Rule,
+};
+
+#[derive(Debug, Args)]
+pub struct LintArgs {
+    /// The output format.
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+    /// Disables a rule.
+    #[arg(short = 'A', long, value_name = "RULE")]
+    allow: Vec<Rule>,
+    /// Reports a rule as a warning, which is the default.
+    #[arg(short = 'W', long, value_name = "RULE")]
+    warn: Vec<Rule>,
+    /// Reports a rule as an error, making the command fail.
+    #[arg(short = 'D', long, value_name = "RULE")]
+    deny: Vec<Rule>,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+    Sarif,
+}
+
+impl LintArgs {
+    pub fn linter(&self) -> Linter {
+        let levels = [
+            (&self.allow, Level::Allow),
+            (&self.warn, Level::Warning),
+            (&self.deny, Level::Error),
+        ];
+        levels
+            .into_iter()
+            .flat_map(|(rules, level)| rules.iter().map(move |rule| (*rule, level)))
+            .fold(Linter::new(), |linter, (rule, level)| {
+                linter.rule(rule, level)
+            })
+    }
+
+    pub fn report(&self, diagnostics: &[Diagnostic]) -> serde_json::Result<String> {
+        Ok(match self.format {
+            Format::Text => diagnostics.iter().map(|d| format!("{d}\n")).collect(),
+            Format::Json => json(diagnostics)? + "\n",
+            Format::Sarif => sarif(diagnostics)? + "\n",
+        })
+    }
+}
@@ -0,0 +1,238 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    ffi::OsString,
+    io::{self, Read},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use potenv::{
+    dialect::Dialect,
+    env::{EnvProvider, ProcessEnvProvider},
+    evaluator::Scope,
+    export::shell::Shell,
+    lint::Level,
+    process,
+    secrets::FileSecrets,
+    Potenv,
+};
+
+use self::{export::ExportArgs, lint::LintArgs};
+
+mod export;
+mod lint;
+
+/// Evaluates POSIX-compliant dotenv files.
+#[derive(Debug, Parser)]
+#[command(name = "potenv", version)]
+struct Cli {
+    /// The dotenv file(s) to evaluate, in order.
+    #[arg(short, long = "file", value_name = "FILE", default_value = ".env")]
+    files: Vec<PathBuf>,
+    /// Variables in dotenv files override those from the environment.
+    #[arg(short = 'o', long = "override")]
+    override_env: bool,
+    /// Evaluate the dotenv files in an empty environment.
+    #[arg(short, long)]
+    ignore_environment: bool,
+    /// Accept the `export` keyword before assignments.
+    #[arg(long)]
+    export_prefix: bool,
+    /// Accept `${#NAME}` expansions, evaluating to the length of a value.
+    #[arg(long)]
+    string_length: bool,
+    /// Accept `${NAME#pattern}` and `${NAME%pattern}` expansions, removing a prefix or suffix.
+    #[arg(long)]
+    pattern_removal: bool,
+    /// Accept the non-POSIX Bash `${NAME:offset:length}` and `${NAME/pattern/replacement}` expansions.
+    #[arg(long)]
+    bash_expansions: bool,
+    /// Accept `${NAME|filter}` expansions, transforming a value with the built-in filters.
+    #[arg(long)]
+    filters: bool,
+    /// Define `X` from the content of the file named by every `X_FILE` variable.
+    #[arg(long)]
+    file_secrets: bool,
+    /// Expand `~` and `~user` at the start of values and after `:` to home directories.
+    #[arg(long)]
+    tilde_expansion: bool,
+    /// Parse the dotenv files following the rules of another implementation.
+    #[arg(long, value_enum, default_value_t = DialectArg::Posix)]
+    dialect: DialectArg,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Checks that the dotenv files can be evaluated.
+    Check,
+    /// Prints the evaluated variables as a dotenv file.
+    Eval,
+    /// Prints the evaluated value of a variable.
+    Get {
+        /// The name of the variable.
+        name: String,
+    },
+    /// Prints the evaluated variables in the specified format.
+    Export(ExportArgs),
+    /// Reports likely mistakes in the dotenv files.
+    Lint(LintArgs),
+    /// Prints the dependency graph of the variables in the DOT language.
+    Graph {
+        /// List the variables expected from the environment instead.
+        #[arg(long)]
+        inputs: bool,
+    },
+    /// Prints a template with its expansions substituted by the evaluated variables,
+    /// like `envsubst`.
+    Template {
+        /// The template file. Defaults to the standard input.
+        template: Option<PathBuf>,
+        /// Keep `$NAME` and `${NAME}` as written when `NAME` is undefined.
+        #[arg(short, long)]
+        keep_unknown: bool,
+    },
+    /// Runs a command with the evaluated variables added to its environment.
+    Run {
+        /// Do not inherit the current environment.
+        #[arg(short, long)]
+        clear_env: bool,
+        /// The command to run, followed by its arguments.
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<OsString>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum DialectArg {
+    Posix,
+    Python,
+    Node,
+    Ruby,
+    Compose,
+}
+
+impl From<DialectArg> for Dialect {
+    fn from(value: DialectArg) -> Self {
+        match value {
+            DialectArg::Posix => Dialect::Posix,
+            DialectArg::Python => Dialect::PythonDotenv,
+            DialectArg::Node => Dialect::Node,
+            DialectArg::Ruby => Dialect::Ruby,
+            DialectArg::Compose => Dialect::DockerCompose,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = if cli.ignore_environment {
+        run(
+            configure(Potenv::new(HashMap::new(), cli.override_env), &cli),
+            cli,
+        )
+    } else {
+        run(
+            configure(Potenv::new(ProcessEnvProvider, cli.override_env), &cli),
+            cli,
+        )
+    };
+    match result {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("potenv: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn configure<T: EnvProvider>(potenv: Potenv<T>, cli: &Cli) -> Potenv<T> {
+    potenv
+        .export_prefix(cli.export_prefix)
+        .string_length(cli.string_length)
+        .pattern_removal(cli.pattern_removal)
+        .bash_expansions(cli.bash_expansions)
+        .filters(cli.filters)
+        .tilde_expansion(cli.tilde_expansion)
+        .file_secrets(cli.file_secrets.then(FileSecrets::new))
+        .dialect(cli.dialect.into())
+}
+
+fn run<T: EnvProvider>(potenv: Potenv<T>, cli: Cli) -> Result<ExitCode, Box<dyn Error>> {
+    let files = &cli.files;
+    match cli.command {
+        Command::Check => {
+            potenv.evaluate(files)?.for_each(drop);
+        }
+        Command::Eval => {
+            for (name, value) in potenv.evaluate(files)? {
+                println!("{}={}", name, Shell::Posix.quote(&value));
+            }
+        }
+        Command::Get { name } => match potenv.evaluate(files)?.find(|(n, _)| *n == name) {
+            Some((_, value)) => println!("{}", value),
+            None => {
+                eprintln!("potenv: undefined variable ${}", name);
+                return Ok(ExitCode::FAILURE);
+            }
+        },
+        Command::Export(args) => {
+            let vars: Scope = potenv.evaluate(files)?.collect();
+            print!("{}", args.exporter().export(&vars)?);
+        }
+        Command::Lint(args) => {
+            let diagnostics = potenv.lint(files, &args.linter())?;
+            print!("{}", args.report(&diagnostics)?);
+            if diagnostics.iter().any(|d| d.level == Level::Error) {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        Command::Graph { inputs } => {
+            let graph = potenv.dependencies(files)?;
+            if !inputs {
+                print!("{}", graph.to_dot());
+                return Ok(ExitCode::SUCCESS);
+            }
+            for (name, input) in graph.inputs() {
+                match input.required {
+                    true => println!("{} (required)", name),
+                    false => println!("{}", name),
+                }
+            }
+        }
+        Command::Template {
+            template,
+            keep_unknown,
+        } => {
+            let source = match template {
+                Some(path) => std::fs::read_to_string(path)?,
+                None => {
+                    let mut source = String::new();
+                    io::stdin().read_to_string(&mut source)?;
+                    source
+                }
+            };
+            let template = potenv.template(&source)?.keep_unknown(keep_unknown);
+            print!("{}", potenv.render(files, &template)?);
+        }
+        Command::Run { clear_env, command } => {
+            let mut cmd = potenv.command(files, &command[0], clear_env)?;
+            cmd.args(&command[1..]);
+            match process::exec(&mut cmd) {
+                Ok(code) => std::process::exit(code),
+                Err(err) => {
+                    eprintln!("potenv: {}: {}", command[0].to_string_lossy(), err);
+                    // Same exit codes as POSIX shells when a command cannot be run.
+                    return Ok(match err.kind() {
+                        io::ErrorKind::NotFound => ExitCode::from(127),
+                        _ => ExitCode::from(126),
+                    });
+                }
+            }
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,267 @@
+//! Parses dotenv files following the rules of Docker Compose.
+//!
+//! - Keys are made of letters, digits, `_`, `.`, `-`, `[` and `]`,
+//!   and are separated from values by `=` or `:`.
+//! - A key without a value inherits its value from the environment.
+//! - Single-quoted values are taken verbatim.
+//! - Double-quoted values decode backslash escapes, `\$` escaping a dollar sign.
+//! - In unquoted values, a `#` preceded by a space starts a comment.
+//! - Variables are expanded in unquoted and double-quoted values,
+//!   with `$NAME`, `${NAME}` and the `-`, `:-`, `+`, `:+`, `?` and `:?` operators.
+//!   `$$` escapes a dollar sign.
+
+use std::path::PathBuf;
+
+use super::{characters, flush, skip, syntax_error};
+use crate::{
+    parser::{
+        ast::{Assignment, Expansion, Expression, Operator},
+        ParseError, ParseResult,
+    },
+    tokenizer::{err::ErrorKind, pos::Position},
+};
+
+#[inline(always)]
+fn is_space(ch: char) -> bool {
+    ch.is_whitespace() && ch != '\n'
+}
+
+#[inline(always)]
+fn is_name_start(ch: char) -> bool {
+    ch.is_ascii_alphabetic() || ch == '_'
+}
+
+#[inline(always)]
+fn is_name_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '_'
+}
+
+/// Returns whether a character is accepted in the key of an assignment.
+pub(crate) fn is_key_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || "_.-[]".contains(ch)
+}
+
+pub(crate) fn parse(input: &str, filename: Option<PathBuf>) -> ParseResult<Vec<Assignment>> {
+    let input = input.replace("\r\n", "\n");
+    let mut parser = Parser {
+        input: &input,
+        filename,
+        pos: 0,
+    };
+    let mut nodes = Vec::new();
+    while let Some(node) = parser.parse_statement()? {
+        nodes.push(node);
+    }
+    Ok(nodes)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    filename: Option<PathBuf>,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn parse_statement(&mut self) -> ParseResult<Option<Assignment>> {
+        loop {
+            self.pos = skip(self.input, self.pos, char::is_whitespace);
+            if !self.rest().starts_with('#') {
+                break;
+            }
+            self.pos = skip(self.input, self.pos, |ch| ch != '\n');
+        }
+        if self.pos == self.input.len() {
+            return Ok(None);
+        }
+        if let Some(after) = self.rest().strip_prefix("export") {
+            if after.starts_with(char::is_whitespace) {
+                self.pos = skip(self.input, self.pos + 6, is_space);
+            }
+        }
+        let start = self.pos;
+        let (key_end, separator) = match self
+            .rest()
+            .char_indices()
+            .find(|&(_, ch)| !is_space(ch) && !is_key_char(ch))
+        {
+            Some((i, ch @ ('=' | ':' | '\n'))) => (start + i, Some(ch)),
+            Some((i, ch)) => return Err(self.error(ErrorKind::InvalidCharacter(ch), start + i)),
+            None => (self.input.len(), None),
+        };
+        let name = self.input[start..key_end].trim_end();
+        if let Some(ch) = name.chars().find(|&ch| is_space(ch)) {
+            return Err(self.error(ErrorKind::InvalidCharacter(ch), start));
+        }
+        if name.is_empty() {
+            let ch = separator.unwrap_or('\n');
+            return Err(self.error(ErrorKind::InvalidCharacter(ch), key_end));
+        }
+        self.pos = key_end + separator.map_or(0, char::len_utf8);
+        let value = match separator {
+            Some('=' | ':') => self.parse_value()?,
+            // A key without a value is passed through from the environment.
+            _ => vec![Expression::Expansion(Expansion::plain(name.to_owned()))],
+        };
+        let position = Position::at_offset(self.input, start);
+        Ok(Some(Assignment::new(name.to_owned(), value).at(position)))
+    }
+
+    fn parse_value(&mut self) -> ParseResult<Vec<Expression>> {
+        self.pos = skip(self.input, self.pos, is_space);
+        let start = self.pos;
+        let quote = match self.rest().chars().next() {
+            Some(quote @ ('\'' | '"')) => quote,
+            _ => {
+                self.pos = skip(self.input, start, |ch| ch != '\n');
+                let value = &self.input[start..self.pos];
+                let value = value.split_once(" #").map_or(value, |(v, _)| v);
+                return self.interpolate(value.trim_end(), start);
+            }
+        };
+        let mut escaped = false;
+        for (i, ch) in self.rest().char_indices().skip(1) {
+            if ch != quote || escaped {
+                escaped = !escaped && ch == '\\';
+                continue;
+            }
+            let value = &self.input[start + 1..start + i];
+            self.pos = start + i + 1;
+            return match quote {
+                '"' => self.interpolate(&expand_escapes(value), start),
+                _ => Ok(characters(value.to_owned())),
+            };
+        }
+        Err(self.error(
+            match quote {
+                '"' => ErrorKind::UnterminatedDoubleQuotedString,
+                _ => ErrorKind::UnterminatedSingleQuotedString,
+            },
+            start,
+        ))
+    }
+
+    fn interpolate(&self, value: &str, offset: usize) -> ParseResult<Vec<Expression>> {
+        interpolate(value).map_err(|kind| self.error(kind, offset))
+    }
+
+    fn rest(&self) -> &str {
+        &self.input[self.pos..]
+    }
+
+    fn error(&self, kind: ErrorKind, offset: usize) -> ParseError {
+        syntax_error(kind, self.input, offset, &self.filename)
+    }
+}
+
+/// Decodes backslash escapes, replacing `\$` by `$$` for interpolation.
+fn expand_escapes(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            result.push(ch);
+            continue;
+        }
+        let Some(next) = chars.next() else {
+            result.push(ch);
+            break;
+        };
+        match next {
+            '$' => result.push_str("$$"),
+            'a' => result.push('\x07'),
+            'b' => result.push('\x08'),
+            'f' => result.push('\x0c'),
+            'n' => result.push('\n'),
+            'r' => result.push('\r'),
+            't' => result.push('\t'),
+            'v' => result.push('\x0b'),
+            '\\' | '"' => result.push(next),
+            _ => {
+                result.push(ch);
+                result.push(next);
+            }
+        }
+    }
+    result
+}
+
+fn interpolate(value: &str) -> Result<Vec<Expression>, ErrorKind> {
+    interpolate_until(value, false).map(|(nodes, _)| nodes)
+}
+
+/// Expands variables up to the end of the input, or up to an unmatched closing brace if `nested`.
+/// Returns the expressions along with the length of the input they span.
+fn interpolate_until(value: &str, nested: bool) -> Result<(Vec<Expression>, usize), ErrorKind> {
+    let mut nodes = Vec::new();
+    let mut chars = String::new();
+    let mut pos = 0;
+    while let Some(ch) = value[pos..].chars().next() {
+        match ch {
+            '}' if nested => {
+                flush(&mut nodes, chars);
+                return Ok((nodes, pos));
+            }
+            '$' => {
+                let rest = &value[pos + 1..];
+                match rest.chars().next() {
+                    Some('$') => {
+                        chars.push('$');
+                        pos += 2;
+                    }
+                    Some('{') => {
+                        let (expansion, len) = parse_braced(&rest[1..])?;
+                        flush(&mut nodes, std::mem::take(&mut chars));
+                        nodes.push(Expression::Expansion(expansion));
+                        pos += 2 + len;
+                    }
+                    Some(ch) if is_name_start(ch) => {
+                        let len = rest.find(|ch| !is_name_char(ch)).unwrap_or(rest.len());
+                        flush(&mut nodes, std::mem::take(&mut chars));
+                        nodes.push(Expression::Expansion(Expansion::plain(
+                            rest[..len].to_owned(),
+                        )));
+                        pos += 1 + len;
+                    }
+                    _ => {
+                        chars.push('$');
+                        pos += 1;
+                    }
+                }
+            }
+            ch => {
+                chars.push(ch);
+                pos += ch.len_utf8();
+            }
+        }
+    }
+    if nested {
+        return Err(ErrorKind::UnterminatedExpansion);
+    }
+    flush(&mut nodes, chars);
+    Ok((nodes, pos))
+}
+
+/// Parses a braced expansion after its `${` prefix, returning it along with its length.
+fn parse_braced(input: &str) -> Result<(Expansion, usize), ErrorKind> {
+    let name_len = match input.chars().next() {
+        Some(ch) if is_name_start(ch) => input.find(|ch| !is_name_char(ch)).unwrap_or(input.len()),
+        Some(ch) => return Err(ErrorKind::InvalidCharacter(ch)),
+        None => return Err(ErrorKind::UnterminatedExpansion),
+    };
+    let name = input[..name_len].to_owned();
+    let rest = &input[name_len..];
+    let (operator, op_len) = match rest.as_bytes() {
+        [b'}', ..] => return Ok((Expansion::plain(name), name_len + 1)),
+        [b':', b'-', ..] => (Operator::IfUnsetOrNull, 2),
+        [b':', b'+', ..] => (Operator::IfSetAndNotNull, 2),
+        [b':', b'?', ..] => (Operator::ErrorIfUnsetOrNull, 2),
+        [b'-', ..] => (Operator::IfUnset, 1),
+        [b'+', ..] => (Operator::IfSet, 1),
+        [b'?', ..] => (Operator::ErrorIfUnset, 1),
+        [] => return Err(ErrorKind::UnterminatedExpansion),
+        _ => return Err(ErrorKind::InvalidCharacter(rest.chars().next().unwrap())),
+    };
+    let (rhs, rhs_len) = interpolate_until(&rest[op_len..], true)?;
+    let len = name_len + op_len + rhs_len + 1;
+    Ok((Expansion::new(name, operator, rhs), len))
+}
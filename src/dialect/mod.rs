@@ -0,0 +1,210 @@
+//! Dialects following the rules of other dotenv implementations,
+//! for files that rely on syntax the POSIX specification forbids.
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use potenv::{dialect::Dialect, evaluator::Evaluator, options::Options, parser::parse_with_options};
+//!
+//! let options = Options { dialect: Dialect::PythonDotenv, ..Default::default() };
+//! let ast = parse_with_options("GREETING = \"hello\\tworld\" # comment", None, options).unwrap();
+//! let env = HashMap::new();
+//! let mut evaluator = Evaluator::new(&env, false);
+//! evaluator.evaluate(ast).unwrap();
+//! assert_eq!("hello\tworld", evaluator.into_scope()["GREETING"]);
+//! ```
+
+use std::path::PathBuf;
+
+use crate::{
+    parser::{ast::Expression, ParseError},
+    tokenizer::{
+        err::{ErrorKind, SyntaxError},
+        pos::Position,
+    },
+};
+
+pub(crate) mod compose;
+pub(crate) mod node;
+pub(crate) mod python;
+pub(crate) mod ruby;
+#[cfg(test)]
+mod tests;
+
+/// The rules used to parse dotenv files.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dialect {
+    /// The POSIX-compliant dotenv specification.
+    #[default]
+    Posix,
+    /// The rules of [python-dotenv](https://github.com/theskumar/python-dotenv).
+    PythonDotenv,
+    /// The rules of the [dotenv](https://github.com/motdotla/dotenv) package for Node.js.
+    Node,
+    /// The rules of the [dotenv](https://github.com/bkeepers/dotenv) gem for Ruby.
+    Ruby,
+    /// The rules of Docker Compose for `.env` files and the `env_file` attribute.
+    DockerCompose,
+}
+
+fn syntax_error(
+    kind: ErrorKind,
+    input: &str,
+    offset: usize,
+    filename: &Option<PathBuf>,
+) -> ParseError {
+    ParseError::Syntax(SyntaxError::new(
+        kind,
+        Position::at_offset(input, offset),
+        filename.clone(),
+    ))
+}
+
+/// Returns an assignment value holding the given characters, if any.
+fn characters(value: String) -> Vec<Expression> {
+    let mut nodes = Vec::new();
+    flush(&mut nodes, value);
+    nodes
+}
+
+/// Pushes the given characters to an expression list, unless they are empty.
+fn flush(nodes: &mut Vec<Expression>, chars: String) {
+    if !chars.is_empty() {
+        nodes.push(Expression::Characters(chars));
+    }
+}
+
+/// Returns the offset of the start of the line following the one containing `offset`.
+fn next_line(input: &str, offset: usize) -> usize {
+    match input[offset..].find('\n') {
+        Some(i) => offset + i + 1,
+        None => input.len(),
+    }
+}
+
+/// Returns the offsets of the quotes that can close a string matching the regular expression
+/// `q(?:\\q|[^q])*q`, whose content starts at `start`.
+///
+/// Offsets are returned in the order a backtracking regex engine tries them:
+/// first the leftmost unescaped quote, then the escaped ones from right to left.
+fn closing_quotes(input: &str, start: usize, quote: char) -> Vec<usize> {
+    let mut escaped = Vec::new();
+    let mut chars = input[start..].char_indices();
+    while let Some((i, ch)) = chars.next() {
+        if ch == '\\' && input[start + i + 1..].starts_with(quote) {
+            escaped.push(start + i + 1);
+            chars.next();
+        } else if ch == quote {
+            return std::iter::once(start + i)
+                .chain(escaped.into_iter().rev())
+                .collect();
+        }
+    }
+    escaped.reverse();
+    escaped
+}
+
+/// Removes matching quotes surrounding a value, returning the quote that was removed.
+fn strip_quotes<'a>(value: &'a str, quotes: &[char]) -> (Option<char>, &'a str) {
+    match value.chars().next() {
+        Some(q) if quotes.contains(&q) && value.len() > 1 && value.ends_with(q) => {
+            (Some(q), &value[1..value.len() - 1])
+        }
+        _ => (None, value),
+    }
+}
+
+/// An assignment matched by [match_line].
+#[derive(Debug)]
+struct Line<'a> {
+    key: &'a str,
+    key_start: usize,
+    value: &'a str,
+    value_start: usize,
+    end: usize,
+}
+
+/// Matches an assignment at the start of a line,
+/// emulating the regular expression shared by the Node.js and Ruby implementations:
+///
+/// ```text
+/// ^\s*(?:export\s+)?(KEY+)(?:\s*=\s*?|:\s+?)(\s*'(?:\\'|[^'])*'|\s*"(?:\\"|[^"])*"|[^#\r\n]+)?\s*(?:#.*)?$
+/// ```
+fn match_line<'a>(
+    input: &'a str,
+    offset: usize,
+    is_key_char: fn(char) -> bool,
+    quotes: &[char],
+) -> Option<Line<'a>> {
+    let start = skip(input, offset, char::is_whitespace);
+    let rest = &input[start..];
+    if let Some(after) = rest.strip_prefix("export") {
+        if after.starts_with(char::is_whitespace) {
+            let key_start = skip(input, start + 6, char::is_whitespace);
+            if let Some(line) = match_assignment(input, key_start, is_key_char, quotes) {
+                return Some(line);
+            }
+        }
+    }
+    match_assignment(input, start, is_key_char, quotes)
+}
+
+fn match_assignment<'a>(
+    input: &'a str,
+    start: usize,
+    is_key_char: fn(char) -> bool,
+    quotes: &[char],
+) -> Option<Line<'a>> {
+    let key_end = skip(input, start, is_key_char);
+    if key_end == start {
+        return None;
+    }
+    let after_key = skip(input, key_end, char::is_whitespace);
+    let value_start = if input[after_key..].starts_with('=') {
+        after_key + 1
+    } else {
+        let mut chars = input[key_end..].chars();
+        match (chars.next(), chars.next()) {
+            (Some(':'), Some(ch)) if ch.is_whitespace() => key_end + 1 + ch.len_utf8(),
+            _ => return None,
+        }
+    };
+    let line = |value_end: usize, end: usize| Line {
+        key: &input[start..key_end],
+        key_start: start,
+        value: &input[value_start..value_end],
+        value_start,
+        end,
+    };
+    let quote_start = skip(input, value_start, char::is_whitespace);
+    if let Some(quote) = input[quote_start..].chars().next() {
+        if quotes.contains(&quote) {
+            for close in closing_quotes(input, quote_start + 1, quote) {
+                if let Some(end) = match_line_end(input, close + 1) {
+                    return Some(line(close + 1, end));
+                }
+            }
+        }
+    }
+    let value_end = skip(input, value_start, |ch| !matches!(ch, '#' | '\r' | '\n'));
+    if value_end > value_start {
+        return match_line_end(input, value_end).map(|end| line(value_end, end));
+    }
+    match_line_end(input, value_start).map(|end| line(value_start, end))
+}
+
+/// Matches optional blanks and an optional comment up to the end of the line.
+fn match_line_end(input: &str, offset: usize) -> Option<usize> {
+    let pos = skip(input, offset, |ch| ch.is_whitespace() && ch != '\n');
+    match input[pos..].chars().next() {
+        None | Some('\n') => Some(pos),
+        Some('#') => Some(skip(input, pos, |ch| ch != '\n')),
+        Some(_) => None,
+    }
+}
+
+/// Returns the offset of the first character from `offset` not matching the predicate.
+fn skip(input: &str, offset: usize, predicate: impl Fn(char) -> bool) -> usize {
+    input[offset..]
+        .find(|ch| !predicate(ch))
+        .map_or(input.len(), |i| offset + i)
+}
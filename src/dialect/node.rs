@@ -0,0 +1,44 @@
+//! Parses dotenv files following the rules of the dotenv package for Node.js.
+//!
+//! - Keys are made of ASCII alphanumerics, `_`, `.` and `-`,
+//!   and are separated from values by `=` or by `:` and whitespace.
+//! - Values can be quoted with single quotes, double quotes or backticks.
+//!   Quoted values are taken verbatim, except for `\n` and `\r`
+//!   which are decoded in double-quoted values.
+//! - In unquoted values, a `#` starts a comment.
+//! - No expansion is performed, and invalid lines are ignored.
+
+use super::{characters, match_line, next_line, strip_quotes};
+use crate::{parser::ast::Assignment, tokenizer::pos::Position};
+
+fn is_key_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || matches!(ch, '_' | '.' | '-')
+}
+
+pub(crate) fn parse(input: &str) -> Vec<Assignment> {
+    let input = input.replace("\r\n", "\n").replace('\r', "\n");
+    let mut nodes = Vec::new();
+    let mut pos = 0;
+    while pos < input.len() {
+        match match_line(&input, pos, is_key_char, &['\'', '"', '`']) {
+            Some(line) => {
+                let value = line.value.trim();
+                // Escapes are decoded even if the value does not end with a double quote.
+                let double_quoted = value.starts_with('"');
+                let (_, value) = strip_quotes(value, &['\'', '"', '`']);
+                let value = if double_quoted {
+                    value.replace("\\n", "\n").replace("\\r", "\r")
+                } else {
+                    value.to_owned()
+                };
+                nodes.push(
+                    Assignment::new(line.key.to_owned(), characters(value))
+                        .at(Position::at_offset(&input, line.key_start)),
+                );
+                pos = next_line(&input, line.end);
+            }
+            None => pos = next_line(&input, pos),
+        }
+    }
+    nodes
+}
@@ -0,0 +1,198 @@
+//! Parses dotenv files following the rules of python-dotenv.
+//!
+//! - Whitespace around `=` is ignored, and keys can be single-quoted.
+//! - Single-quoted values decode `\\` and `\'`, double-quoted values
+//!   also decode `\"`, `\a`, `\b`, `\f`, `\n`, `\r`, `\t` and `\v`.
+//! - In unquoted values, a `#` preceded by whitespace starts a comment.
+//! - `${NAME}` and `${NAME:-default}` are expanded in all values.
+//!   The default value is used only when the variable is unset.
+//! - Invalid lines and keys without a value are ignored.
+
+use super::{closing_quotes, flush, skip};
+use crate::{
+    parser::ast::{Assignment, Expansion, Expression, Operator},
+    tokenizer::pos::Position,
+};
+
+#[inline(always)]
+fn is_blank(ch: char) -> bool {
+    ch.is_whitespace() && !matches!(ch, '\r' | '\n')
+}
+
+pub(crate) fn parse(input: &str) -> Vec<Assignment> {
+    let mut nodes = Vec::new();
+    let mut pos = 0;
+    loop {
+        pos = skip(input, pos, char::is_whitespace);
+        if pos == input.len() {
+            return nodes;
+        }
+        let start = pos;
+        match parse_binding(input, &mut pos) {
+            Ok(Some(node)) => nodes.push(node.at(Position::at_offset(input, start))),
+            Ok(None) => (),
+            Err(()) => {
+                pos = skip(input, pos, |ch| !matches!(ch, '\r' | '\n'));
+                pos += end_of_line(&input[pos..]).unwrap_or_default();
+            }
+        }
+    }
+}
+
+/// Parses a binding, leaving `pos` where parsing stopped on error.
+fn parse_binding(input: &str, pos: &mut usize) -> Result<Option<Assignment>, ()> {
+    if let Some(after) = input[*pos..].strip_prefix("export") {
+        if after.starts_with(is_blank) {
+            *pos = skip(input, *pos + 6, is_blank);
+        }
+    }
+    let key = parse_key(input, pos)?;
+    *pos = skip(input, *pos, is_blank);
+    let value = if input[*pos..].starts_with('=') {
+        *pos = skip(input, *pos + 1, is_blank);
+        Some(parse_value(input, pos)?)
+    } else {
+        None
+    };
+    let comment = skip(input, *pos, is_blank);
+    if input[comment..].starts_with('#') {
+        *pos = skip(input, comment, |ch| !matches!(ch, '\r' | '\n'));
+    }
+    let eol = skip(input, *pos, is_blank);
+    *pos = eol + end_of_line(&input[eol..]).ok_or(())?;
+    Ok(key
+        .zip(value)
+        .map(|(name, value)| Assignment::new(name.to_owned(), interpolate(&value))))
+}
+
+/// Returns the length of the line terminator at the start of the input.
+fn end_of_line(input: &str) -> Option<usize> {
+    if input.is_empty() {
+        Some(0)
+    } else if input.starts_with("\r\n") {
+        Some(2)
+    } else if input.starts_with(['\r', '\n']) {
+        Some(1)
+    } else {
+        None
+    }
+}
+
+fn parse_key<'a>(input: &'a str, pos: &mut usize) -> Result<Option<&'a str>, ()> {
+    let start = *pos;
+    match input[start..].chars().next() {
+        Some('#') => Ok(None),
+        Some('\'') => {
+            let end = skip(input, start + 1, |ch| ch != '\'');
+            if end == start + 1 || end == input.len() {
+                return Err(());
+            }
+            *pos = end + 1;
+            Ok(Some(&input[start + 1..end]))
+        }
+        _ => {
+            let end = skip(input, start, |ch| {
+                !matches!(ch, '=' | '#') && !ch.is_whitespace()
+            });
+            if end == start {
+                return Err(());
+            }
+            *pos = end;
+            Ok(Some(&input[start..end]))
+        }
+    }
+}
+
+fn parse_value(input: &str, pos: &mut usize) -> Result<String, ()> {
+    let start = *pos;
+    match input[start..].chars().next() {
+        Some(quote @ ('\'' | '"')) => {
+            let end = *closing_quotes(input, start + 1, quote).first().ok_or(())?;
+            *pos = end + 1;
+            Ok(decode_escapes(&input[start + 1..end], quote))
+        }
+        None | Some('\r' | '\n') => Ok(String::new()),
+        _ => {
+            let end = skip(input, start, |ch| !matches!(ch, '\r' | '\n'));
+            *pos = end;
+            let value = &input[start..end];
+            let comment = value
+                .char_indices()
+                .skip(1)
+                .find(|&(i, ch)| ch == '#' && value[..i].ends_with(char::is_whitespace))
+                .map_or(value.len(), |(i, _)| i);
+            Ok(value[..comment].trim_end().to_owned())
+        }
+    }
+}
+
+fn decode_escapes(value: &str, quote: char) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            result.push(ch);
+            continue;
+        }
+        let decoded = match (quote, chars.peek()) {
+            (_, Some('\\')) => '\\',
+            (_, Some('\'')) => '\'',
+            ('"', Some('"')) => '"',
+            ('"', Some('a')) => '\x07',
+            ('"', Some('b')) => '\x08',
+            ('"', Some('f')) => '\x0c',
+            ('"', Some('n')) => '\n',
+            ('"', Some('r')) => '\r',
+            ('"', Some('t')) => '\t',
+            ('"', Some('v')) => '\x0b',
+            _ => {
+                result.push(ch);
+                continue;
+            }
+        };
+        chars.next();
+        result.push(decoded);
+    }
+    result
+}
+
+/// Expands `${NAME}` and `${NAME:-default}` variables.
+fn interpolate(value: &str) -> Vec<Expression> {
+    let mut nodes = Vec::new();
+    let mut chars = String::new();
+    let mut rest = value;
+    while let Some(i) = rest.find("${") {
+        let (literal, variable) = rest.split_at(i);
+        match parse_variable(&variable[2..]) {
+            Some((expansion, len)) => {
+                chars.push_str(literal);
+                flush(&mut nodes, std::mem::take(&mut chars));
+                nodes.push(Expression::Expansion(expansion));
+                rest = &variable[2 + len..];
+            }
+            None => {
+                chars.push_str(&rest[..i + 1]);
+                rest = &rest[i + 1..];
+            }
+        }
+    }
+    chars.push_str(rest);
+    flush(&mut nodes, chars);
+    nodes
+}
+
+/// Parses a variable after its `${` prefix, returning it along with its length.
+fn parse_variable(input: &str) -> Option<(Expansion, usize)> {
+    let name_end = input.find(['}', ':'])?;
+    let name = input[..name_end].to_owned();
+    let rest = &input[name_end..];
+    if rest.starts_with('}') {
+        return Some((Expansion::plain(name), name_end + 1));
+    }
+    let default = rest.strip_prefix(":-")?;
+    let default_end = default.find('}')?;
+    let mut rhs = Vec::new();
+    flush(&mut rhs, default[..default_end].to_owned());
+    let len = name_end + 2 + default_end + 1;
+    Some((Expansion::new(name, Operator::IfUnset, rhs), len))
+}
@@ -0,0 +1,150 @@
+//! Parses dotenv files following the rules of the dotenv gem for Ruby.
+//!
+//! - Keys are made of ASCII alphanumerics, `_` and `.`,
+//!   and are separated from values by `=` or by `:` and whitespace.
+//! - Single-quoted values are taken verbatim.
+//! - Double-quoted values decode `\n` and `\r`, then remove backslashes
+//!   like unquoted values do, except in front of `$`.
+//! - `$NAME` and `${NAME}` are expanded in unquoted and double-quoted values,
+//!   unless the `$` is escaped by a backslash.
+//! - In unquoted values, a `#` starts a comment.
+//! - Invalid lines are ignored, but command substitutions are rejected.
+
+use std::path::PathBuf;
+
+use super::{characters, flush, match_line, next_line, strip_quotes, syntax_error};
+use crate::{
+    parser::{
+        ast::{Assignment, Expansion, Expression},
+        ParseResult,
+    },
+    tokenizer::{err::ErrorKind, pos::Position},
+};
+
+fn is_key_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || matches!(ch, '_' | '.')
+}
+
+#[inline(always)]
+fn is_space(ch: char) -> bool {
+    matches!(ch, '\0' | '\t' | '\n' | '\x0b' | '\x0c' | '\r' | ' ')
+}
+
+pub(crate) fn parse(input: &str, filename: Option<PathBuf>) -> ParseResult<Vec<Assignment>> {
+    let mut nodes = Vec::new();
+    let mut pos = 0;
+    while pos < input.len() {
+        let Some(line) = match_line(input, pos, is_key_char, &['\'', '"']) else {
+            pos = next_line(input, pos);
+            continue;
+        };
+        let value = match strip_quotes(line.value.trim_matches(is_space), &['\'', '"']) {
+            (Some('\''), value) => characters(value.to_owned()),
+            (quote, value) => {
+                let value = match quote {
+                    Some(_) => value.replace("\\n", "\n").replace("\\r", "\r"),
+                    None => value.to_owned(),
+                };
+                substitute_commands(&unescape(&value))
+                    .map(|value| interpolate(&value))
+                    .map_err(|kind| syntax_error(kind, input, line.value_start, &filename))?
+            }
+        };
+        nodes.push(
+            Assignment::new(line.key.to_owned(), value)
+                .at(Position::at_offset(input, line.key_start)),
+        );
+        pos = next_line(input, line.end);
+    }
+    Ok(nodes)
+}
+
+/// Removes backslashes, except in front of `$`.
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match (ch, chars.peek()) {
+            ('\\', Some(&next)) if next != '$' => {
+                result.push(next);
+                chars.next();
+            }
+            _ => result.push(ch),
+        }
+    }
+    result
+}
+
+/// Removes the backslash from escaped command substitutions,
+/// and rejects unescaped ones since they would be executed.
+fn substitute_commands(value: &str) -> Result<String, ErrorKind> {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(i) = rest.find("$(") {
+        let Some(len) = match_command(&rest[i + 1..]) else {
+            result.push_str(&rest[..i + 2]);
+            rest = &rest[i + 2..];
+            continue;
+        };
+        match rest[..i].strip_suffix('\\') {
+            Some(literal) => result.push_str(literal),
+            None => return Err(ErrorKind::UnsupportedCommandExpansion),
+        }
+        result.push_str(&rest[i..i + 1 + len]);
+        rest = &rest[i + 1 + len..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Matches non-empty balanced parentheses, returning their length.
+fn match_command(input: &str) -> Option<usize> {
+    let mut pos = 1;
+    while let Some(ch) = input[pos..].chars().next() {
+        match ch {
+            '(' => pos += match_command(&input[pos..])?,
+            ')' if pos == 1 => return None,
+            ')' => return Some(pos + 1),
+            _ => pos += ch.len_utf8(),
+        }
+    }
+    None
+}
+
+/// Expands `$NAME` and `${NAME}` variables.
+fn interpolate(value: &str) -> Vec<Expression> {
+    let mut nodes = Vec::new();
+    let mut chars = String::new();
+    let mut rest = value;
+    while let Some(i) = rest.find('$') {
+        let after = &rest[i + 1..];
+        if after.starts_with('(') {
+            chars.push_str(&rest[..i + 1]);
+            rest = after;
+            continue;
+        }
+        let name_start = usize::from(after.starts_with('{'));
+        let name_end = name_start
+            + after[name_start..]
+                .find(|ch: char| !ch.is_ascii_alphanumeric() && ch != '_')
+                .unwrap_or(after.len() - name_start);
+        let len = name_end + usize::from(after[name_end..].starts_with('}'));
+        match rest[..i].strip_suffix('\\') {
+            Some(literal) => {
+                chars.push_str(literal);
+                chars.push_str(&rest[i..i + 1 + len]);
+            }
+            None if name_end > name_start => {
+                chars.push_str(&rest[..i]);
+                flush(&mut nodes, std::mem::take(&mut chars));
+                let name = after[name_start..name_end].to_owned();
+                nodes.push(Expression::Expansion(Expansion::plain(name)));
+            }
+            None => chars.push_str(&rest[..i + 1 + len]),
+        }
+        rest = &after[len..];
+    }
+    chars.push_str(rest);
+    flush(&mut nodes, chars);
+    nodes
+}
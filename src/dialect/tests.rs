@@ -0,0 +1,61 @@
+use rstest::rstest;
+
+use super::{closing_quotes, compose, match_line, ruby};
+use crate::{parser::ParseError, tokenizer::err::ErrorKind};
+
+#[rstest]
+#[case::unescaped(r#""a\"b" c""#, vec![5, 3])]
+#[case::escaped_only(r#""a\"b\""#, vec![6, 3])]
+#[case::double_backslash(r#""a\\"b""#, vec![6, 4])]
+#[case::unterminated(r#""ab"#, vec![])]
+fn test_closing_quotes(#[case] input: &str, #[case] expected: Vec<usize>) {
+    assert_eq!(expected, closing_quotes(input, 1, '"'));
+}
+
+#[rstest]
+#[case::export("export A=b", Some(("A", "b")))]
+#[case::export_as_key("export=b", Some(("export", "b")))]
+#[case::leading_blank_lines("\n\n  A=b", Some(("A", "b")))]
+#[case::backtracking_quotes(r#"A="b\" c"#, Some(("A", r#""b\" c"#)))]
+#[case::colon_without_space("A:b", None)]
+#[case::no_separator("A b", None)]
+fn test_match_line(#[case] input: &str, #[case] expected: Option<(&str, &str)>) {
+    let line = match_line(input, 0, |ch| ch.is_ascii_alphanumeric(), &['"']);
+    assert_eq!(expected, line.map(|l| (l.key, l.value.trim())));
+}
+
+#[rstest]
+#[case::unterminated_quote("A=1\nB='c", ErrorKind::UnterminatedSingleQuotedString, 2, 3)]
+#[case::space_in_key("A=1\n  B C=1", ErrorKind::InvalidCharacter(' '), 2, 3)]
+#[case::invalid_key_char("A=1\nB$=1", ErrorKind::InvalidCharacter('$'), 2, 2)]
+#[case::empty_key("=1", ErrorKind::InvalidCharacter('='), 1, 1)]
+#[case::non_ascii_key("A=1\nCAFÉ=1", ErrorKind::InvalidCharacter('É'), 2, 4)]
+#[case::invalid_expansion("A=${1}", ErrorKind::InvalidCharacter('1'), 1, 3)]
+#[case::unterminated_expansion("A=\"${B:-c\"", ErrorKind::UnterminatedExpansion, 1, 3)]
+fn test_compose_errors(
+    #[case] input: &str,
+    #[case] kind: ErrorKind,
+    #[case] line: usize,
+    #[case] column: usize,
+) {
+    let err = compose::parse(input, None).unwrap_err();
+    match err {
+        ParseError::Syntax(err) => {
+            assert_eq!(kind, err.kind());
+            assert_eq!((line, column), (err.line(), err.column()));
+        }
+        err => panic!("Unexpected error {err:?}"),
+    }
+}
+
+#[test]
+fn test_ruby_command_error() {
+    let err = ruby::parse("A=1\nB= \"$(echo (a))\"", None).unwrap_err();
+    match err {
+        ParseError::Syntax(err) => {
+            assert_eq!(ErrorKind::UnsupportedCommandExpansion, err.kind());
+            assert_eq!((2, 3), (err.line(), err.column()));
+        }
+        err => panic!("Unexpected error {err:?}"),
+    }
+}
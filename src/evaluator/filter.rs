@@ -0,0 +1,189 @@
+//! Named transforms applied to the value of a variable by `${NAME|filter:argument}` expansions.
+//!
+//! ```
+//! use potenv::evaluator::filter::{Filters, Value};
+//!
+//! let mut filters = Filters::default();
+//! filters.register("reverse", |value, _| match value {
+//!     Value::String(s) => Ok(Value::String(s.chars().rev().collect())),
+//!     Value::List(items) => Ok(Value::List(items.into_iter().rev().collect())),
+//! });
+//! assert!(filters.get("reverse").is_some());
+//! assert!(filters.get("upper").is_some());
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{Arc, OnceLock},
+};
+
+#[cfg(feature = "base64")]
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+/// The value passed between the filters of an expansion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    String(String),
+    List(Vec<String>),
+}
+
+impl Value {
+    /// Converts the value to a string, joining the items of a list with commas.
+    pub fn into_string(self) -> String {
+        match self {
+            Self::String(s) => s,
+            Self::List(items) => items.join(","),
+        }
+    }
+}
+
+/// A filter, receiving the value and the optional argument following the filter name.
+/// Errors are reported as the message of an [super::EvaluationError::Filter].
+pub type FilterFn = dyn Fn(Value, Option<&str>) -> Result<Value, String> + Send + Sync;
+
+/// A registry of named filters.
+/// The default registry contains the built-in filters:
+///
+/// * `upper`, `lower` and `trim`, applied to every item of a list;
+/// * `split:separator`, splitting a string into a list, on commas by default;
+/// * `join:separator`, joining a list into a string, with commas by default;
+/// * `first` and `last`, selecting an item of a list;
+/// * `base64encode` and `base64decode`, with the `base64` feature.
+#[derive(Clone)]
+pub struct Filters {
+    filters: BTreeMap<String, Arc<FilterFn>>,
+}
+
+impl Filters {
+    /// Creates a registry without any filter.
+    pub fn empty() -> Self {
+        Self {
+            filters: BTreeMap::new(),
+        }
+    }
+
+    /// Registers a filter, replacing any filter of the same name.
+    pub fn register<F>(&mut self, name: impl Into<String>, filter: F)
+    where
+        F: Fn(Value, Option<&str>) -> Result<Value, String> + Send + Sync + 'static,
+    {
+        self.filters.insert(name.into(), Arc::new(filter));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&FilterFn> {
+        self.filters.get(name).map(Arc::as_ref)
+    }
+}
+
+impl Default for Filters {
+    fn default() -> Self {
+        static BUILTINS: OnceLock<Filters> = OnceLock::new();
+        BUILTINS.get_or_init(builtins).clone()
+    }
+}
+
+fn builtins() -> Filters {
+    let mut filters = Filters::empty();
+    filters.register("upper", |value, arg| {
+        map(value, arg, |s| Ok(s.to_uppercase()))
+    });
+    filters.register("lower", |value, arg| {
+        map(value, arg, |s| Ok(s.to_lowercase()))
+    });
+    filters.register("trim", |value, arg| {
+        map(value, arg, |s| Ok(s.trim().to_owned()))
+    });
+    filters.register("split", |value, arg| {
+        let separator = arg.unwrap_or(",");
+        if separator.is_empty() {
+            return Err("the separator cannot be empty".to_owned());
+        }
+        let items = match value {
+            Value::String(s) => vec![s],
+            Value::List(items) => items,
+        };
+        Ok(Value::List(
+            items
+                .iter()
+                .filter(|s| !s.is_empty())
+                .flat_map(|s| s.split(separator))
+                .map(str::to_owned)
+                .collect(),
+        ))
+    });
+    filters.register("join", |value, arg| match value {
+        Value::List(items) => Ok(Value::String(items.join(arg.unwrap_or(",")))),
+        value => Ok(value),
+    });
+    filters.register("first", |value, arg| {
+        no_argument(arg)?;
+        match value {
+            Value::List(items) => Ok(Value::String(items.into_iter().next().unwrap_or_default())),
+            value => Ok(value),
+        }
+    });
+    filters.register("last", |value, arg| {
+        no_argument(arg)?;
+        match value {
+            Value::List(items) => Ok(Value::String(items.into_iter().last().unwrap_or_default())),
+            value => Ok(value),
+        }
+    });
+    #[cfg(feature = "base64")]
+    {
+        filters.register("base64encode", |value, arg| {
+            map(value, arg, |s| Ok(STANDARD.encode(s)))
+        });
+        filters.register("base64decode", |value, arg| {
+            map(value, arg, |s| {
+                let bytes = STANDARD.decode(s).map_err(|err| err.to_string())?;
+                String::from_utf8(bytes).map_err(|_| "the decoded value is not UTF-8".to_owned())
+            })
+        });
+    }
+    filters
+}
+
+impl fmt::Debug for Filters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.filters.keys()).finish()
+    }
+}
+
+/// Registries are equal when they map the same names to the same functions.
+impl PartialEq for Filters {
+    fn eq(&self, other: &Self) -> bool {
+        self.filters.len() == other.filters.len()
+            && self
+                .filters
+                .iter()
+                .zip(&other.filters)
+                .all(|((a, f), (b, g))| a == b && Arc::ptr_eq(f, g))
+    }
+}
+
+impl Eq for Filters {}
+
+/// Applies a string transform to a string, or to every item of a list.
+fn map<F>(value: Value, arg: Option<&str>, f: F) -> Result<Value, String>
+where
+    F: Fn(&str) -> Result<String, String>,
+{
+    no_argument(arg)?;
+    match value {
+        Value::String(s) => f(&s).map(Value::String),
+        Value::List(items) => items
+            .iter()
+            .map(|s| f(s))
+            .collect::<Result<_, _>>()
+            .map(Value::List),
+    }
+}
+
+fn no_argument(arg: Option<&str>) -> Result<(), String> {
+    match arg {
+        None => Ok(()),
+        Some(_) => Err("the filter does not take an argument".to_owned()),
+    }
+}
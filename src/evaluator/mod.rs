@@ -1,16 +1,31 @@
-use std::collections::HashMap;
+use std::path::PathBuf;
 
+use indexmap::IndexMap;
 use thiserror::Error;
 
+use self::{
+    filter::{Filters, Value},
+    pattern::Pattern,
+    resolver::Resolvers,
+};
 use crate::{
     env::EnvProvider,
-    parser::ast::{Assignment, Expansion, Expression, Operator},
+    parser::ast::{
+        Assignment, Expansion, Expression, Filtered, Operator, Replacement, ReplacementMode,
+        Resolved, Substring, Tilde,
+    },
+    tokenizer::pos::Position,
+    vars::{Env, Provenance},
 };
 
+pub mod filter;
+mod pattern;
+pub mod resolver;
 #[cfg(test)]
 mod tests;
 
-pub type Scope = HashMap<String, String>;
+/// Evaluated variables, in declaration order.
+pub type Scope = IndexMap<String, String>;
 
 #[derive(Debug, Error)]
 pub enum EvaluationError {
@@ -18,6 +33,14 @@ pub enum EvaluationError {
     UndefinedVariable(String, String),
     #[error("Missing required value for variable ${0} {1}")]
     EmptyValue(String, String),
+    #[error("Unknown filter '{1}' applied to variable ${0}")]
+    UnknownFilter(String, String),
+    #[error("Filter '{1}' failed on variable ${0}: {2}")]
+    Filter(String, String, String),
+    #[error("No resolver registered for namespace '{0}'")]
+    UnknownResolver(String),
+    #[error("Resolver '{0}' failed for argument '{1}': {2}")]
+    Resolver(String, String, String),
 }
 
 type EvaluationResult<T> = Result<T, EvaluationError>;
@@ -30,6 +53,11 @@ where
     env: &'a T,
     scope: Scope,
     override_env: bool,
+    filters: Filters,
+    resolvers: Resolvers,
+    /// The file of the assignments being evaluated.
+    file: Option<PathBuf>,
+    provenances: IndexMap<String, Provenance>,
 }
 
 impl<'a, T> Evaluator<'a, T>
@@ -40,10 +68,35 @@ where
         Self {
             env,
             override_env,
-            scope: HashMap::new(),
+            scope: Scope::new(),
+            filters: Filters::default(),
+            resolvers: Resolvers::default(),
+            file: None,
+            provenances: IndexMap::new(),
         }
     }
 
+    /// Sets the filters available to `${NAME|filter}` expansions.
+    /// Defaults to the built-in filters.
+    pub fn filters(mut self, filters: Filters) -> Self {
+        self.filters = filters;
+        self
+    }
+
+    /// Sets the resolvers of `${namespace:argument}` expansions.
+    /// Defaults to no resolvers.
+    pub fn resolvers(mut self, resolvers: Resolvers) -> Self {
+        self.resolvers = resolvers;
+        self
+    }
+
+    /// Sets the variables already defined, e.g. by previously evaluated files.
+    /// Defaults to an empty scope.
+    pub fn scope(mut self, scope: Scope) -> Self {
+        self.scope = scope;
+        self
+    }
+
     pub fn evaluate(&mut self, ast: Vec<Assignment>) -> EvaluationResult<()> {
         for node in ast {
             self.evaluate_assignment(node)?;
@@ -51,18 +104,49 @@ where
         Ok(())
     }
 
-    pub fn into_scope(self) -> HashMap<String, String> {
+    /// Sets the file of the assignments evaluated next, as recorded in their [Provenance].
+    pub fn set_file(&mut self, file: Option<PathBuf>) {
+        self.file = file;
+    }
+
+    pub fn into_scope(self) -> Scope {
         self.scope
     }
 
+    /// Returns the evaluated variables along with their provenance.
+    /// Variables of the initial scope are considered to come from the environment.
+    pub fn into_env(mut self) -> Env {
+        self.scope
+            .into_iter()
+            .map(|(name, value)| {
+                let provenance = self
+                    .provenances
+                    .swap_remove(&name)
+                    .unwrap_or(Provenance::Environment);
+                (name, value, provenance)
+            })
+            .collect()
+    }
+
+    /// Evaluates a value, such as the value of an assignment or a template.
+    /// Assignments made by `${NAME:=word}` expansions are added to the scope.
+    pub fn evaluate_value(&mut self, value: Vec<Expression>) -> EvaluationResult<String> {
+        self.evaluate_expression(value)
+    }
+
     fn evaluate_assignment(&mut self, node: Assignment) -> EvaluationResult<()> {
         let name = node.name;
-        let value = if let Some(v) = (!self.override_env).then(|| self.env.var(&name)).flatten() {
-            v
-        } else {
-            self.evaluate_expression(node.value)?
-        };
-        self.scope.insert(name, value);
+        let (value, provenance) =
+            if let Some(v) = (!self.override_env).then(|| self.env.var(&name)).flatten() {
+                (v, Provenance::Environment)
+            } else {
+                (
+                    self.evaluate_expression(node.value)?,
+                    self.here(node.position),
+                )
+            };
+        self.scope.insert(name.clone(), value);
+        self.provenances.insert(name, provenance);
         Ok(())
     }
 
@@ -72,12 +156,39 @@ where
             let value = match node {
                 Expression::Characters(chars) => chars,
                 Expression::Expansion(expr) => self.evaluate_expansion(expr)?,
+                Expression::Length(node) => self
+                    .resolve(&node.name)
+                    .unwrap_or_default()
+                    .chars()
+                    .count()
+                    .to_string(),
+                Expression::Substring(node) => self.evaluate_substring(node),
+                Expression::Replacement(node) => self.evaluate_replacement(node)?,
+                Expression::Filtered(node) => self.evaluate_filtered(node)?,
+                Expression::Resolved(node) => self.evaluate_resolved(node)?,
+                Expression::Tilde(node) => self.evaluate_tilde(node),
+                Expression::Quoted(node) => self.evaluate_expression(vec![*node])?,
             };
             result.push_str(&value);
         }
         Ok(result)
     }
 
+    /// Evaluates a pattern, escaping the values of quoted expansions so that they match literally.
+    fn evaluate_pattern(&mut self, nodes: Vec<Expression>) -> EvaluationResult<Pattern> {
+        let mut pattern = String::with_capacity(64);
+        for node in nodes {
+            match node {
+                Expression::Quoted(node) => {
+                    let value = self.evaluate_expression(vec![*node])?;
+                    pattern.push_str(&pattern::escape(&value));
+                }
+                node => pattern.push_str(&self.evaluate_expression(vec![node])?),
+            }
+        }
+        Ok(Pattern::new(&pattern))
+    }
+
     fn evaluate_expansion(&mut self, expr: Expansion) -> EvaluationResult<String> {
         let value = self.resolve(&expr.name);
         let result = match expr.operator {
@@ -98,11 +209,11 @@ where
                 _ => self.evaluate_expression(expr.rhs)?,
             },
             Operator::AssignIfUnset => match value {
-                None => self.assign_op(expr.name, expr.rhs)?,
+                None => self.assign_op(expr.name, expr.rhs, expr.position)?,
                 _ => value.unwrap(),
             },
             Operator::AssignIfUnsetOrNull => match value.as_deref() {
-                None | Some("") => self.assign_op(expr.name, expr.rhs)?,
+                None | Some("") => self.assign_op(expr.name, expr.rhs, expr.position)?,
                 _ => value.unwrap(),
             },
             Operator::ErrorIfUnset => match value {
@@ -114,11 +225,94 @@ where
                 Some("") => self.error_op(expr.name, expr.rhs, true)?,
                 _ => value.unwrap(),
             },
+            Operator::RemoveSmallestPrefix
+            | Operator::RemoveLargestPrefix
+            | Operator::RemoveSmallestSuffix
+            | Operator::RemoveLargestSuffix => {
+                let value = value.unwrap_or_default();
+                let pattern = self.evaluate_pattern(expr.rhs)?;
+                match expr.operator {
+                    Operator::RemoveSmallestPrefix => pattern.strip_prefix(&value, false),
+                    Operator::RemoveLargestPrefix => pattern.strip_prefix(&value, true),
+                    Operator::RemoveSmallestSuffix => pattern.strip_suffix(&value, false),
+                    _ => pattern.strip_suffix(&value, true),
+                }
+                .to_owned()
+            }
+        };
+        Ok(result)
+    }
+
+    fn evaluate_substring(&self, node: Substring) -> String {
+        let chars: Vec<char> = self
+            .resolve(&node.name)
+            .unwrap_or_default()
+            .chars()
+            .collect();
+        let len = chars.len() as i64;
+        let start = match node.offset {
+            offset if offset < 0 => len + offset,
+            offset => offset,
+        };
+        let end = match node.length {
+            None => len,
+            Some(length) if length < 0 => len + length,
+            Some(length) => start.saturating_add(length).min(len),
+        };
+        // Unlike Bash, out of range offsets result in an empty string rather than an error.
+        if start < 0 || start >= end {
+            return String::new();
+        }
+        chars[start as usize..end as usize].iter().collect()
+    }
+
+    fn evaluate_replacement(&mut self, node: Replacement) -> EvaluationResult<String> {
+        let Some(value) = self.resolve(&node.name) else {
+            return Ok(String::new());
+        };
+        let pattern = self.evaluate_pattern(node.pattern)?;
+        let replacement = self.evaluate_expression(node.replacement)?;
+        let result = match node.mode {
+            ReplacementMode::First => pattern.replace(&value, &replacement, false),
+            ReplacementMode::All => pattern.replace(&value, &replacement, true),
+            ReplacementMode::Prefix => pattern.replace_prefix(&value, &replacement),
+            ReplacementMode::Suffix => pattern.replace_suffix(&value, &replacement),
         };
         Ok(result)
     }
 
-    fn resolve(&self, name: &str) -> Option<String> {
+    fn evaluate_filtered(&self, node: Filtered) -> EvaluationResult<String> {
+        let mut value = Value::String(self.resolve(&node.name).unwrap_or_default());
+        for filter in node.filters {
+            let Some(apply) = self.filters.get(&filter.name) else {
+                return Err(EvaluationError::UnknownFilter(node.name, filter.name));
+            };
+            value = apply(value, filter.argument.as_deref()).map_err(|message| {
+                EvaluationError::Filter(node.name.clone(), filter.name, message)
+            })?;
+        }
+        Ok(value.into_string())
+    }
+
+    fn evaluate_resolved(&mut self, node: Resolved) -> EvaluationResult<String> {
+        let argument = self.evaluate_expression(node.argument)?;
+        let Some(resolver) = self.resolvers.get(&node.namespace) else {
+            return Err(EvaluationError::UnknownResolver(node.namespace));
+        };
+        resolver(&argument)
+            .map_err(|message| EvaluationError::Resolver(node.namespace, argument, message))
+    }
+
+    /// Expands to the home directory of the user, or is kept literally if it is unknown.
+    fn evaluate_tilde(&self, node: Tilde) -> String {
+        let home = match node.user.as_str() {
+            "" => self.resolve("HOME"),
+            user => user_home(user),
+        };
+        home.unwrap_or_else(|| format!("~{}", node.user))
+    }
+
+    pub(crate) fn resolve(&self, name: &str) -> Option<String> {
         if self.override_env {
             self.scope
                 .get(name)
@@ -131,12 +325,26 @@ where
         }
     }
 
-    fn assign_op(&mut self, name: String, expr: Vec<Expression>) -> EvaluationResult<String> {
+    fn assign_op(
+        &mut self,
+        name: String,
+        expr: Vec<Expression>,
+        position: Position,
+    ) -> EvaluationResult<String> {
         let value = self.evaluate_expression(expr)?;
-        self.scope.insert(name, value.clone());
+        self.scope.insert(name.clone(), value.clone());
+        self.provenances.insert(name, self.here(position));
         Ok(value)
     }
 
+    /// Returns the provenance of a variable assigned at the given position of the current file.
+    fn here(&self, position: Position) -> Provenance {
+        Provenance::File {
+            file: self.file.clone(),
+            position,
+        }
+    }
+
     fn error_op(
         &mut self,
         name: String,
@@ -151,3 +359,46 @@ where
         }
     }
 }
+
+/// Looks up the home directory of a user in the system user database,
+/// which can also be backed by e.g. LDAP or NIS.
+#[cfg(unix)]
+fn user_home(user: &str) -> Option<String> {
+    use std::{
+        ffi::{CStr, CString},
+        mem::MaybeUninit,
+        ptr,
+    };
+
+    let name = CString::new(user).ok()?;
+    let mut buf: Vec<libc::c_char> = vec![0; 1024];
+    loop {
+        let mut entry = MaybeUninit::<libc::passwd>::uninit();
+        let mut result = ptr::null_mut();
+        // SAFETY: every pointer is valid, and the length is the one of the buffer.
+        let code = unsafe {
+            libc::getpwnam_r(
+                name.as_ptr(),
+                entry.as_mut_ptr(),
+                buf.as_mut_ptr(),
+                buf.len(),
+                &mut result,
+            )
+        };
+        match code {
+            libc::ERANGE if buf.len() < 1 << 20 => buf.resize(buf.len() * 2, 0),
+            0 if !result.is_null() => {
+                // SAFETY: the entry is initialized, and its strings point into the buffer.
+                let home = unsafe { CStr::from_ptr((*result).pw_dir) };
+                return home.to_str().ok().map(str::to_owned);
+            }
+            _ => return None,
+        }
+    }
+}
+
+/// Other platforms have no user database, so `~user` is kept literally.
+#[cfg(not(unix))]
+fn user_home(_user: &str) -> Option<String> {
+    None
+}
@@ -0,0 +1,288 @@
+//! The pattern matching notation of POSIX shells,
+//! as used by pattern removal and replacement expansions.
+//!
+//! See <https://pubs.opengroup.org/onlinepubs/9699919799/utilities/V3_chap02.html#tag_18_13>
+
+#[derive(Debug, Clone)]
+enum Token {
+    Char(char),
+    /// `?`
+    Any,
+    /// `*`
+    Star,
+    /// `[...]`
+    Bracket {
+        negated: bool,
+        items: Vec<BracketItem>,
+    },
+}
+
+#[derive(Debug, Clone)]
+enum BracketItem {
+    Char(char),
+    Range(char, char),
+    Class(fn(char) -> bool),
+}
+
+impl BracketItem {
+    fn matches(&self, ch: char) -> bool {
+        match self {
+            Self::Char(c) => *c == ch,
+            Self::Range(start, end) => (*start..=*end).contains(&ch),
+            Self::Class(predicate) => predicate(ch),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Pattern {
+    tokens: Vec<Token>,
+}
+
+impl Pattern {
+    pub fn new(pattern: &str) -> Self {
+        let chars: Vec<char> = pattern.chars().collect();
+        let mut tokens = Vec::with_capacity(chars.len());
+        let mut i = 0;
+        while i < chars.len() {
+            let token = match chars[i] {
+                '\\' if i + 1 < chars.len() => {
+                    i += 1;
+                    Token::Char(chars[i])
+                }
+                '?' => Token::Any,
+                '*' => Token::Star,
+                '[' => match parse_bracket(&chars[i + 1..]) {
+                    Some((token, len)) => {
+                        i += len;
+                        token
+                    }
+                    // An unterminated bracket expression matches a literal `[`.
+                    None => Token::Char('['),
+                },
+                c => Token::Char(c),
+            };
+            tokens.push(token);
+            i += 1;
+        }
+        Self { tokens }
+    }
+
+    /// Returns whether the pattern matches the whole text.
+    pub fn matches(&self, text: &str) -> bool {
+        let text: Vec<char> = text.chars().collect();
+        let (mut t, mut p) = (0, 0);
+        // The position of the last star in the pattern, and of the text it was tried at.
+        let mut backtrack = None;
+        while t < text.len() {
+            match self.tokens.get(p) {
+                Some(Token::Star) => {
+                    backtrack = Some((p, t));
+                    p += 1;
+                }
+                Some(token) if token_matches(token, text[t]) => {
+                    p += 1;
+                    t += 1;
+                }
+                _ => match backtrack {
+                    // Let the last star match one more character.
+                    Some((star, start)) => {
+                        backtrack = Some((star, start + 1));
+                        p = star + 1;
+                        t = start + 1;
+                    }
+                    None => return false,
+                },
+            }
+        }
+        self.tokens[p..]
+            .iter()
+            .all(|token| matches!(token, Token::Star))
+    }
+
+    /// Removes the smallest or largest prefix of the text matching the pattern.
+    pub fn strip_prefix<'a>(&self, text: &'a str, largest: bool) -> &'a str {
+        let mut ends = boundaries(text);
+        let end = match largest {
+            true => ends.rev().find(|&end| self.matches(&text[..end])),
+            false => ends.find(|&end| self.matches(&text[..end])),
+        };
+        end.map_or(text, |end| &text[end..])
+    }
+
+    /// Removes the smallest or largest suffix of the text matching the pattern.
+    pub fn strip_suffix<'a>(&self, text: &'a str, largest: bool) -> &'a str {
+        let mut starts = boundaries(text);
+        let start = match largest {
+            true => starts.find(|&start| self.matches(&text[start..])),
+            false => starts.rev().find(|&start| self.matches(&text[start..])),
+        };
+        start.map_or(text, |start| &text[..start])
+    }
+
+    /// Replaces the first or all of the longest non-empty matches of the pattern in the text.
+    pub fn replace(&self, text: &str, replacement: &str, all: bool) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let mut result = String::with_capacity(text.len());
+        let mut start = 0;
+        while start < chars.len() {
+            match self.longest_prefix(&chars[start..]) {
+                Some(len) if len > 0 => {
+                    result.push_str(replacement);
+                    start += len;
+                    if !all {
+                        break;
+                    }
+                }
+                _ => {
+                    result.push(chars[start]);
+                    start += 1;
+                }
+            }
+        }
+        result.extend(&chars[start..]);
+        result
+    }
+
+    /// Replaces the largest prefix of the text matching the pattern.
+    pub fn replace_prefix(&self, text: &str, replacement: &str) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        match self.longest_prefix(&chars) {
+            Some(len) => format!("{replacement}{}", chars[len..].iter().collect::<String>()),
+            None => text.to_owned(),
+        }
+    }
+
+    /// Returns the length of the longest prefix of the text matching the pattern, if any,
+    /// in a single pass tracking every position of the pattern reachable so far.
+    fn longest_prefix(&self, text: &[char]) -> Option<usize> {
+        let len = self.tokens.len();
+        let mut states = vec![false; len + 1];
+        states[0] = true;
+        self.skip_stars(&mut states);
+        let mut longest = states[len].then_some(0);
+        let mut next = vec![false; len + 1];
+        for (i, &ch) in text.iter().enumerate() {
+            next.fill(false);
+            for (p, token) in self.tokens.iter().enumerate() {
+                if !states[p] {
+                    continue;
+                }
+                match token {
+                    Token::Star => next[p] = true,
+                    token if token_matches(token, ch) => next[p + 1] = true,
+                    _ => (),
+                }
+            }
+            self.skip_stars(&mut next);
+            if next[len] {
+                longest = Some(i + 1);
+            }
+            if !next.contains(&true) {
+                break;
+            }
+            std::mem::swap(&mut states, &mut next);
+        }
+        longest
+    }
+
+    /// Adds the positions following stars, which can match an empty string.
+    fn skip_stars(&self, states: &mut [bool]) {
+        for (p, token) in self.tokens.iter().enumerate() {
+            if states[p] && matches!(token, Token::Star) {
+                states[p + 1] = true;
+            }
+        }
+    }
+
+    /// Replaces the largest suffix of the text matching the pattern.
+    pub fn replace_suffix(&self, text: &str, replacement: &str) -> String {
+        match boundaries(text).find(|&start| self.matches(&text[start..])) {
+            Some(start) => format!("{}{replacement}", &text[..start]),
+            None => text.to_owned(),
+        }
+    }
+}
+
+/// Escapes the characters of the text that are special in a pattern, so that it matches literally.
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if matches!(ch, '*' | '?' | '[' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+/// Returns the byte offsets of the character boundaries of the text, including its end.
+fn boundaries(text: &str) -> impl DoubleEndedIterator<Item = usize> + '_ {
+    text.char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(text.len()))
+}
+
+fn token_matches(token: &Token, ch: char) -> bool {
+    match token {
+        Token::Char(c) => *c == ch,
+        Token::Any => true,
+        Token::Star => false,
+        Token::Bracket { negated, items } => items.iter().any(|item| item.matches(ch)) != *negated,
+    }
+}
+
+/// Parses a bracket expression following its opening `[`,
+/// returning it along with the number of characters it spans, including the closing `]`.
+fn parse_bracket(chars: &[char]) -> Option<(Token, usize)> {
+    let mut i = 0;
+    let negated = matches!(chars.first(), Some('!' | '^'));
+    if negated {
+        i += 1;
+    }
+    let mut items = Vec::new();
+    let first = i;
+    loop {
+        let ch = *chars.get(i)?;
+        match ch {
+            // A `]` at the start of the expression is a literal.
+            ']' if i > first => return Some((Token::Bracket { negated, items }, i + 1)),
+            '\\' if i + 1 < chars.len() => {
+                items.push(BracketItem::Char(chars[i + 1]));
+                i += 2;
+            }
+            '[' if chars.get(i + 1) == Some(&':') => {
+                let name_len = chars[i + 2..].windows(2).position(|w| w == [':', ']'])?;
+                let name: String = chars[i + 2..i + 2 + name_len].iter().collect();
+                items.push(BracketItem::Class(char_class(&name)?));
+                i += name_len + 4;
+            }
+            _ if chars.get(i + 1) == Some(&'-') && chars.get(i + 2).is_some_and(|c| *c != ']') => {
+                items.push(BracketItem::Range(ch, chars[i + 2]));
+                i += 3;
+            }
+            _ => {
+                items.push(BracketItem::Char(ch));
+                i += 1;
+            }
+        }
+    }
+}
+
+fn char_class(name: &str) -> Option<fn(char) -> bool> {
+    Some(match name {
+        "alnum" => |c: char| c.is_alphanumeric(),
+        "alpha" => |c: char| c.is_alphabetic(),
+        "blank" => |c: char| c == ' ' || c == '\t',
+        "cntrl" => |c: char| c.is_control(),
+        "digit" => |c: char| c.is_ascii_digit(),
+        "graph" => |c: char| !c.is_control() && !c.is_whitespace(),
+        "lower" => |c: char| c.is_lowercase(),
+        "print" => |c: char| !c.is_control(),
+        "punct" => |c: char| c.is_ascii_punctuation(),
+        "space" => |c: char| c.is_whitespace(),
+        "upper" => |c: char| c.is_uppercase(),
+        "xdigit" => |c: char| c.is_ascii_hexdigit(),
+        _ => return None,
+    })
+}
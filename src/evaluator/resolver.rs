@@ -0,0 +1,58 @@
+//! Functions providing the values of `${namespace:argument}` expansions,
+//! for values coming from other sources than the environment.
+//!
+//! ```
+//! use potenv::evaluator::resolver::Resolvers;
+//!
+//! let mut resolvers = Resolvers::default();
+//! resolvers.register("file", |path| {
+//!     std::fs::read_to_string(path).map_err(|err| err.to_string())
+//! });
+//! assert!(resolvers.get("file").is_some());
+//! ```
+
+use std::{collections::BTreeMap, fmt, sync::Arc};
+
+/// A resolver, receiving the evaluated argument of the expansion.
+/// Errors are reported as the message of an [super::EvaluationError::Resolver].
+pub type ResolverFn = dyn Fn(&str) -> Result<String, String> + Send + Sync;
+
+/// A registry of resolvers by namespace. There are no built-in resolvers.
+#[derive(Clone, Default)]
+pub struct Resolvers {
+    resolvers: BTreeMap<String, Arc<ResolverFn>>,
+}
+
+impl Resolvers {
+    /// Registers a resolver, replacing any resolver of the same namespace.
+    pub fn register<F>(&mut self, namespace: impl Into<String>, resolver: F)
+    where
+        F: Fn(&str) -> Result<String, String> + Send + Sync + 'static,
+    {
+        self.resolvers.insert(namespace.into(), Arc::new(resolver));
+    }
+
+    pub fn get(&self, namespace: &str) -> Option<&ResolverFn> {
+        self.resolvers.get(namespace).map(Arc::as_ref)
+    }
+}
+
+impl fmt::Debug for Resolvers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.resolvers.keys()).finish()
+    }
+}
+
+/// Registries are equal when they map the same namespaces to the same functions.
+impl PartialEq for Resolvers {
+    fn eq(&self, other: &Self) -> bool {
+        self.resolvers.len() == other.resolvers.len()
+            && self
+                .resolvers
+                .iter()
+                .zip(&other.resolvers)
+                .all(|((a, f), (b, g))| a == b && Arc::ptr_eq(f, g))
+    }
+}
+
+impl Eq for Resolvers {}
@@ -1,12 +1,21 @@
 use std::collections::HashMap;
 
+use rstest::rstest;
 use serde::Deserialize;
 use thiserror::Error;
 
-use super::{EvaluationError, Evaluator, Scope};
+use super::{
+    filter::{Filters, Value},
+    pattern::Pattern,
+    resolver::Resolvers,
+    EvaluationError, Evaluator,
+};
 use crate::{
-    parser::{parse, ParseError},
+    options::Options,
+    parser::{parse, parse_with_options, ParseError},
     test_utils::{collect_spec_files, load_spec_file, AnyRes},
+    tokenizer::pos::Position,
+    vars::Provenance,
 };
 
 macro_rules! scope {
@@ -17,6 +26,8 @@ macro_rules! scope {
     }};
 }
 
+type Env = HashMap<String, String>;
+
 #[derive(Debug, Error)]
 enum EvalError {
     #[error(transparent)]
@@ -25,11 +36,33 @@ enum EvalError {
     Eval(#[from] EvaluationError),
 }
 
-fn eval(input: &str, env: Scope, override_env: bool) -> Result<Scope, EvalError> {
+fn eval(input: &str, env: Env, override_env: bool) -> Result<Env, EvalError> {
     let mut eval = Evaluator::new(&env, override_env);
     let ast = parse(input, Some("<test>".into()))?;
     eval.evaluate(ast)?;
-    Ok(eval.into_scope())
+    Ok(eval.into_scope().into_iter().collect())
+}
+
+/// Evaluates the input parsed with the options, and returns the value of `X`.
+fn eval_with(options: Options, input: &str) -> Result<String, EvaluationError> {
+    eval_with_filters(options, input, Filters::default())
+}
+
+fn eval_with_filters(
+    options: Options,
+    input: &str,
+    filters: Filters,
+) -> Result<String, EvaluationError> {
+    let ast = parse_with_options(input, None, options).unwrap();
+    let mut resolvers = Resolvers::default();
+    resolvers.register("upper", |arg| Ok(arg.to_uppercase()));
+    resolvers.register("fail", |arg| Err(format!("cannot resolve '{arg}'")));
+    let env = Env::from([("HOME".to_owned(), "/home/me".to_owned())]);
+    let mut eval = Evaluator::new(&env, true)
+        .filters(filters)
+        .resolvers(resolvers);
+    eval.evaluate(ast)?;
+    Ok(eval.into_scope()["X"].clone())
 }
 
 #[test]
@@ -118,3 +151,256 @@ fn assert_spec_err(case: ErrorCase) -> Result<(), EvalError> {
     println!("Ok");
     Ok(())
 }
+
+#[rstest]
+#[case::literal("abc", "abc", true)]
+#[case::literal_mismatch("abc", "abd", false)]
+#[case::any("a?c", "abc", true)]
+#[case::any_requires_char("a?", "a", false)]
+#[case::star("a*c", "abbbc", true)]
+#[case::star_empty("a*", "a", true)]
+#[case::stars("*b*d", "abcd", true)]
+#[case::star_backtracking("*ab", "aab", true)]
+#[case::bracket("[abc]x", "bx", true)]
+#[case::bracket_range("[a-c0-9]", "7", true)]
+#[case::bracket_negated("[!a-c]", "b", false)]
+#[case::bracket_leading_bracket("[]a]", "]", true)]
+#[case::bracket_class("[[:upper:]_]*", "_x", true)]
+#[case::bracket_unterminated("[a", "[a", true)]
+#[case::escaped(r"\*\?", "*?", true)]
+#[case::escaped_mismatch(r"\*", "a", false)]
+#[case::unicode("é*", "été", true)]
+fn test_pattern_matches(#[case] pattern: &str, #[case] text: &str, #[case] expected: bool) {
+    assert_eq!(expected, Pattern::new(pattern).matches(text));
+}
+
+#[rstest]
+#[case::first("a*", "baaab", false, "b_")]
+#[case::all("a", "baaab", true, "b___b")]
+#[case::whole("*", "abc", false, "_")]
+#[case::any("?", "ab", true, "__")]
+#[case::longest("[ab]*c", "xabcabc", false, "x_")]
+#[case::backtracking("*ab", "aabab", true, "_")]
+#[case::no_match("x*", "abc", true, "abc")]
+#[case::empty_text("*", "", true, "")]
+#[case::unicode("é?", "aéte", true, "a_e")]
+fn test_pattern_replace(
+    #[case] pattern: &str,
+    #[case] text: &str,
+    #[case] all: bool,
+    #[case] expected: &str,
+) {
+    assert_eq!(expected, Pattern::new(pattern).replace(text, "_", all));
+}
+
+#[test]
+fn test_pattern_replace_long_text() {
+    let text = "a".repeat(1000);
+    assert_eq!(text, Pattern::new("*a*b").replace(&text, "_", true));
+    assert_eq!("_", Pattern::new("*a").replace(&text, "_", true));
+}
+
+#[rstest]
+#[case::smallest_prefix("${A#*/}", "a/b/c")]
+#[case::largest_prefix("${A##*/}", "c")]
+#[case::smallest_suffix("${A%/*}", "/a/b")]
+#[case::largest_suffix("${A%%/*}", "")]
+#[case::no_match("${A#x}", "/a/b/c")]
+#[case::empty_pattern("${A%}", "/a/b/c")]
+#[case::quoted_pattern("${A#'/'*}", "a/b/c")]
+#[case::literal_star("${B%'*'}", "x")]
+#[case::unset("${C%/}", "")]
+#[case::expansion_in_pattern("${A#$D}", "/b/c")]
+#[case::unquoted_expansion("${B%$S}", "x*")]
+#[case::quoted_expansion("${B%\"$S\"}", "x")]
+#[case::quoted_expansion_largest("${B%%\"$S\"}", "x")]
+#[case::quoted_expansion_prefix("${B#\"${S}\"}", "x*")]
+#[case::quoted_default("${B%\"${E:-*}\"}", "x")]
+#[case::quoted_outside_pattern("\"${B%%$S}\"", "")]
+fn test_pattern_removal(#[case] expression: &str, #[case] expected: &str) {
+    let options = Options {
+        pattern_removal: true,
+        ..Default::default()
+    };
+    let input = format!("A=/a/b/c B='x*' D=/a S='*'\nX={expression}");
+    assert_eq!(expected, eval_with(options, &input).unwrap());
+}
+
+#[rstest]
+#[case::offset("${A:2}", "cdéf")]
+#[case::offset_and_length("${A:1:3}", "bcd")]
+#[case::negative_offset("${A: -2}", "éf")]
+#[case::negative_length("${A:1:-2}", "bcd")]
+#[case::long_length("${A:4:10}", "éf")]
+#[case::offset_past_end("${A:7}", "")]
+#[case::negative_offset_past_start("${A: -7}", "")]
+#[case::end_before_start("${A:4:-3}", "")]
+#[case::unset("${C:1}", "")]
+#[case::replace_first("${B/o/0}", "f0o-bar-foo")]
+#[case::replace_all("${B//o/0}", "f00-bar-f00")]
+#[case::replace_longest("${B/o*-/}", "ffoo")]
+#[case::remove_all("${B//-}", "foobarfoo")]
+#[case::replace_prefix("${B/#foo/x}", "x-bar-foo")]
+#[case::replace_suffix("${B/%foo/x}", "foo-bar-x")]
+#[case::no_prefix_match("${B/#bar/x}", "foo-bar-foo")]
+#[case::empty_prefix("${B/#/x}", "xfoo-bar-foo")]
+#[case::empty_pattern("${B//}", "foo-bar-foo")]
+#[case::quoted_pattern("${D/'*'/x}", "ax*")]
+#[case::unquoted_expansion("${D/$S/_}", "_")]
+#[case::quoted_expansion("${D/\"$S\"/_}", "a_*")]
+#[case::quoted_expansion_all("${D//\"$S\"/_}", "a__")]
+#[case::quoted_expansion_and_star("${D/\"$S\"*/_}", "a_")]
+#[case::quoted_expansion_suffix("${D/%\"$S\"/_}", "a*_")]
+#[case::expansion_in_replacement("${B/bar/$A}", "foo-abcdéf-foo")]
+#[case::unset_replacement("${C/x/y}", "")]
+fn test_bash_expansions(#[case] expression: &str, #[case] expected: &str) {
+    let options = Options {
+        bash_expansions: true,
+        ..Default::default()
+    };
+    let input = format!("A=abcdéf B=foo-bar-foo D='a**' S='*'\nX={expression}");
+    assert_eq!(expected, eval_with(options, &input).unwrap());
+}
+
+fn evaluate_filters(expression: &str, filters: Filters) -> Result<String, EvaluationError> {
+    let options = Options {
+        filters: true,
+        ..Default::default()
+    };
+    let input = format!("A=' a,B , c ' S=aGVsbG8= I=invalid\nX={expression}");
+    eval_with_filters(options, &input, filters)
+}
+
+#[rstest]
+#[case::upper("${A|upper}", " A,B , C ")]
+#[case::trim("${A|trim}", "a,B , c")]
+#[case::split_first("${A|split:,|first}", " a")]
+#[case::split_last_trim("${A|split|last|trim}", "c")]
+#[case::list_map("${A|split:,|trim|lower}", "a,b,c")]
+#[case::join("${A|split|trim|join:;}", "a;B;c")]
+#[case::first_of_string("${A|first}", " a,B , c ")]
+#[case::split_unset("${U|split|first}", "")]
+fn test_filters(#[case] expression: &str, #[case] expected: &str) {
+    let value = evaluate_filters(expression, Filters::default()).unwrap();
+    assert_eq!(expected, value);
+}
+
+#[cfg(feature = "base64")]
+#[rstest]
+#[case::base64decode("${S|base64decode}", Ok("hello"))]
+#[case::base64encode("${S|base64decode|base64encode}", Ok("aGVsbG8="))]
+#[case::invalid_base64("${I|base64decode}", Err(()))]
+fn test_base64_filters(#[case] expression: &str, #[case] expected: Result<&str, ()>) {
+    let value = evaluate_filters(expression, Filters::default());
+    assert_eq!(expected, value.as_deref().map_err(|_| ()));
+}
+
+#[cfg(not(feature = "base64"))]
+#[test]
+fn test_base64_filters_disabled() {
+    let err = evaluate_filters("${S|base64decode}", Filters::default()).unwrap_err();
+    assert!(matches!(err, EvaluationError::UnknownFilter(..)), "{err:?}");
+}
+
+#[rstest]
+#[case::unknown("${A|reverse}")]
+#[case::unexpected_argument("${A|upper:x}")]
+#[case::empty_separator("${A|split:}")]
+fn test_filter_errors(#[case] expression: &str) {
+    let err = evaluate_filters(expression, Filters::default()).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            EvaluationError::UnknownFilter(..) | EvaluationError::Filter(..)
+        ),
+        "{err:?}"
+    );
+}
+
+#[test]
+fn test_custom_filter() {
+    let mut filters = Filters::empty();
+    filters.register("reverse", |value, _| {
+        Ok(Value::String(value.into_string().chars().rev().collect()))
+    });
+    let value = evaluate_filters("${A|reverse}", filters.clone()).unwrap();
+    assert_eq!(" c , B,a ", value);
+    let err = evaluate_filters("${A|upper}", filters).unwrap_err();
+    assert!(matches!(err, EvaluationError::UnknownFilter(..)), "{err:?}");
+}
+
+fn evaluate_resolvers(input: &str) -> Result<String, EvaluationError> {
+    let options = Options {
+        resolvers: true,
+        ..Default::default()
+    };
+    eval_with(options, input)
+}
+
+#[rstest]
+#[case::literal("X=${upper:abc}", "ABC")]
+#[case::expanded_argument("A=b\nX=${upper:a$A${C:-c}}", "ABC")]
+#[case::quoted_argument("X=${upper:'a b'}", "A B")]
+#[case::empty_argument("X=${upper:}", "")]
+#[case::with_default("X=${upper:-abc}", "abc")]
+fn test_resolvers(#[case] input: &str, #[case] expected: &str) {
+    assert_eq!(expected, evaluate_resolvers(input).unwrap());
+}
+
+#[test]
+fn test_resolver_errors() {
+    let err = evaluate_resolvers("X=${fail:x}").unwrap_err();
+    assert!(
+        matches!(&err, EvaluationError::Resolver(ns, arg, _) if ns == "fail" && arg == "x"),
+        "{err:?}"
+    );
+    let err = evaluate_resolvers("X=${unknown:x}").unwrap_err();
+    assert!(
+        matches!(&err, EvaluationError::UnknownResolver(ns) if ns == "unknown"),
+        "{err:?}"
+    );
+}
+
+#[rstest]
+#[case::home("X=~/bin", "/home/me/bin")]
+#[case::path("X=~/bin:~:/usr/bin", "/home/me/bin:/home/me:/usr/bin")]
+#[case::redefined_home("HOME=/root\nX=~", "/root")]
+#[case::unknown_user("X=~no-such-user/bin", "~no-such-user/bin")]
+#[case::unknown_user_alone("X=~unknownuser", "~unknownuser")]
+#[case::quoted("X='~'", "~")]
+fn test_tilde_expansion(#[case] input: &str, #[case] expected: &str) {
+    let options = Options {
+        tilde_expansion: true,
+        ..Default::default()
+    };
+    assert_eq!(expected, eval_with(options, input).unwrap());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_tilde_expansion_user() {
+    let options = Options {
+        tilde_expansion: true,
+        ..Default::default()
+    };
+    let value = eval_with(options, "X=~root/bin").unwrap();
+    assert!(value.starts_with('/') && value.ends_with("/bin"), "{value}");
+}
+
+#[test]
+fn test_provenance() {
+    let env = scope!("B": "env");
+    let mut eval = Evaluator::new(&env, false);
+    eval.set_file(Some(".env".into()));
+    let ast = parse("A=a\nB=b\nC=${D:=d}", None).unwrap();
+    eval.evaluate(ast).unwrap();
+    let env = eval.into_env();
+    let at = |line, column| Provenance::File {
+        file: Some(".env".into()),
+        position: Position::new(line, column),
+    };
+    assert_eq!(at(1, 1), env.var("A").unwrap().provenance);
+    assert_eq!(Provenance::Environment, env.var("B").unwrap().provenance);
+    assert_eq!(at(3, 1), env.var("C").unwrap().provenance);
+    assert_eq!(at(3, 5), env.var("D").unwrap().provenance);
+}
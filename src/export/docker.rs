@@ -0,0 +1,28 @@
+use std::fmt::Write;
+
+use super::{check_name, ExportError, ExportResult, Exporter};
+use crate::evaluator::Scope;
+
+/// Exports variables in the format of `docker run --env-file`.
+///
+/// Since this format has no quoting, values are written verbatim
+/// and cannot contain line breaks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DockerEnvFile;
+
+impl Exporter for DockerEnvFile {
+    fn export(&self, vars: &Scope) -> ExportResult<String> {
+        let mut out = String::with_capacity(vars.len() * 32);
+        for (name, value) in vars {
+            check_name(name)?;
+            if value.contains(['\n', '\r']) {
+                return Err(ExportError::UnsupportedValue(
+                    name.clone(),
+                    "line breaks cannot be represented in a Docker env-file".into(),
+                ));
+            }
+            let _ = writeln!(out, "{}={}", name, value);
+        }
+        Ok(out)
+    }
+}
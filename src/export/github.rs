@@ -0,0 +1,53 @@
+use std::fmt::Write;
+
+use super::{ExportError, ExportResult, Exporter};
+use crate::evaluator::Scope;
+
+const DELIMITER: &str = "EOF";
+
+/// Exports variables in the format of the files referenced by the `GITHUB_ENV`
+/// and `GITHUB_OUTPUT` environment variables in GitHub Actions workflows.
+///
+/// Multiline values use the heredoc-style syntax,
+/// with a delimiter that does not occur as a line of the value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GithubActions;
+
+impl Exporter for GithubActions {
+    fn export(&self, vars: &Scope) -> ExportResult<String> {
+        let mut out = String::with_capacity(vars.len() * 32);
+        for (name, value) in vars {
+            // GitHub Actions accepts names that are not valid shell identifiers,
+            // i.e. for step outputs, but the name itself cannot be delimited.
+            if name.is_empty() || name.contains(['=', '<', '\n', '\r']) {
+                return Err(ExportError::InvalidName(name.clone()));
+            }
+            // The runner splits lines on carriage returns and joins them back with line feeds.
+            if value.contains('\r') {
+                return Err(ExportError::UnsupportedValue(
+                    name.clone(),
+                    "carriage returns cannot be represented in a GitHub Actions env-file".into(),
+                ));
+            }
+            if value.contains('\n') {
+                let delimiter = delimiter_for(value);
+                let _ = writeln!(out, "{}<<{}\n{}\n{}", name, delimiter, value, delimiter);
+            } else {
+                let _ = writeln!(out, "{}={}", name, value);
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Returns a delimiter that does not collide with any line of the value.
+fn delimiter_for(value: &str) -> String {
+    let lines: Vec<&str> = value.split('\n').collect();
+    let mut delimiter = DELIMITER.to_owned();
+    let mut i = 0;
+    while lines.contains(&delimiter.as_str()) {
+        i += 1;
+        delimiter = format!("{}_{}", DELIMITER, i);
+    }
+    delimiter
+}
@@ -0,0 +1,41 @@
+use super::{tree::Node, ExportResult, Exporter};
+use crate::evaluator::Scope;
+
+/// Exports variables as a JSON object.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Json {
+    separator: Option<String>,
+    pretty: bool,
+}
+
+impl Json {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Nests variables into objects by splitting their names on the given separator,
+    /// i.e. `DB__HOST` becomes `{"DB": {"HOST": "..."}}` when using `__`.
+    pub fn nested(mut self, separator: impl Into<String>) -> Self {
+        self.separator = Some(separator.into());
+        self
+    }
+
+    /// Sets whether the output should be pretty-printed. Defaults to false.
+    pub fn pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
+}
+
+impl Exporter for Json {
+    fn export(&self, vars: &Scope) -> ExportResult<String> {
+        let tree = Node::new(vars, self.separator.as_deref())?;
+        let mut out = if self.pretty {
+            serde_json::to_string_pretty(&tree)?
+        } else {
+            serde_json::to_string(&tree)?
+        };
+        out.push('\n');
+        Ok(out)
+    }
+}
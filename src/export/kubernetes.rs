@@ -0,0 +1,119 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use super::{ExportError, ExportResult, Exporter};
+use crate::evaluator::Scope;
+
+/// Exports variables as a Kubernetes ConfigMap manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigMap {
+    metadata: Metadata,
+}
+
+impl ConfigMap {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            metadata: Metadata::new(name.into()),
+        }
+    }
+
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.metadata.namespace = Some(namespace.into());
+        self
+    }
+}
+
+impl Exporter for ConfigMap {
+    fn export(&self, vars: &Scope) -> ExportResult<String> {
+        let data = check_keys(vars)?
+            .map(|(name, value)| (name.as_str(), value.clone()))
+            .collect();
+        render("ConfigMap", None, &self.metadata, data)
+    }
+}
+
+/// Exports variables as a Kubernetes Secret manifest, with base64-encoded values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Secret {
+    metadata: Metadata,
+}
+
+impl Secret {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            metadata: Metadata::new(name.into()),
+        }
+    }
+
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.metadata.namespace = Some(namespace.into());
+        self
+    }
+}
+
+impl Exporter for Secret {
+    fn export(&self, vars: &Scope) -> ExportResult<String> {
+        let data = check_keys(vars)?
+            .map(|(name, value)| (name.as_str(), STANDARD.encode(value)))
+            .collect();
+        render("Secret", Some("Opaque"), &self.metadata, data)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct Metadata {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    namespace: Option<String>,
+}
+
+impl Metadata {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            namespace: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest<'a> {
+    api_version: &'static str,
+    kind: &'static str,
+    metadata: &'a Metadata,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    secret_type: Option<&'static str>,
+    data: IndexMap<&'a str, String>,
+}
+
+fn render(
+    kind: &'static str,
+    secret_type: Option<&'static str>,
+    metadata: &Metadata,
+    data: IndexMap<&str, String>,
+) -> ExportResult<String> {
+    let manifest = Manifest {
+        api_version: "v1",
+        kind,
+        metadata,
+        secret_type,
+        data,
+    };
+    Ok(serde_yaml::to_string(&manifest)?)
+}
+
+/// ConfigMap and Secret keys must consist of alphanumeric characters, `-`, `_` or `.`.
+fn check_keys(vars: &Scope) -> ExportResult<impl Iterator<Item = (&String, &String)>> {
+    let valid = |name: &str| {
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    };
+    match vars.keys().find(|name| !valid(name)) {
+        Some(name) => Err(ExportError::InvalidName(name.clone())),
+        None => Ok(vars.iter()),
+    }
+}
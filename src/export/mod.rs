@@ -0,0 +1,59 @@
+//! Exporters rendering evaluated variables in formats understood by other tools.
+
+use thiserror::Error;
+
+use crate::{evaluator::Scope, tokenizer::is_identifier};
+
+pub mod docker;
+pub mod github;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(feature = "kubernetes")]
+pub mod kubernetes;
+pub mod pam;
+pub mod shell;
+pub mod systemd;
+#[cfg(test)]
+mod tests;
+#[cfg(feature = "toml")]
+pub mod toml;
+#[cfg(any(feature = "json", feature = "toml", feature = "yaml"))]
+mod tree;
+#[cfg(feature = "yaml")]
+pub mod yaml;
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("Invalid variable name '{0}'")]
+    InvalidName(String),
+    #[error("Variable '{0}' conflicts with another variable when nested")]
+    KeyConflict(String),
+    #[error("Variable '{0}' has an empty key when nested")]
+    EmptyKey(String),
+    #[error("Unsupported value for variable '{0}': {1}")]
+    UnsupportedValue(String, String),
+    #[cfg(feature = "json")]
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[cfg(feature = "toml")]
+    #[error(transparent)]
+    Toml(#[from] ::toml::ser::Error),
+    #[cfg(feature = "yaml")]
+    #[error(transparent)]
+    Yaml(#[from] serde_yaml::Error),
+}
+
+pub type ExportResult<T> = Result<T, ExportError>;
+
+/// Trait for formats that evaluated variables can be exported to.
+pub trait Exporter {
+    fn export(&self, vars: &Scope) -> ExportResult<String>;
+}
+
+fn check_name(name: &str) -> ExportResult<()> {
+    if is_identifier(name) {
+        Ok(())
+    } else {
+        Err(ExportError::InvalidName(name.to_owned()))
+    }
+}
@@ -0,0 +1,35 @@
+use std::fmt::Write;
+
+use super::{check_name, ExportError, ExportResult, Exporter};
+use crate::evaluator::Scope;
+
+/// Exports variables in the `/etc/environment` format read by `pam_env`.
+///
+/// `pam_env` has no escape sequences: it only strips the quote that starts a value
+/// and any quote that ends it, so values are wrapped in double quotes verbatim.
+/// Values cannot contain line breaks, nor `#`, which starts a comment anywhere on a line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EtcEnvironment;
+
+impl Exporter for EtcEnvironment {
+    fn export(&self, vars: &Scope) -> ExportResult<String> {
+        let mut out = String::with_capacity(vars.len() * 32);
+        for (name, value) in vars {
+            check_name(name)?;
+            if value.contains(['\n', '\r']) {
+                return Err(ExportError::UnsupportedValue(
+                    name.clone(),
+                    "line breaks cannot be represented in /etc/environment".into(),
+                ));
+            }
+            if value.contains('#') {
+                return Err(ExportError::UnsupportedValue(
+                    name.clone(),
+                    "'#' starts a comment in /etc/environment".into(),
+                ));
+            }
+            let _ = writeln!(out, "{}=\"{}\"", name, value);
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,83 @@
+use std::fmt::Write;
+
+use super::{check_name, ExportResult, Exporter};
+use crate::evaluator::Scope;
+
+/// Exports variables as shell commands, i.e. for `eval "$(potenv export)"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Posix,
+    Bash,
+    Zsh,
+    Fish,
+    PowerShell,
+    Nushell,
+}
+
+impl Shell {
+    /// Quotes a value so that it is read back verbatim by the shell.
+    pub fn quote(&self, value: &str) -> String {
+        match self {
+            Self::Posix | Self::Bash | Self::Zsh => {
+                format!("'{}'", value.replace('\'', r"'\''"))
+            }
+            Self::Fish => format!("'{}'", value.replace('\\', r"\\").replace('\'', r"\'")),
+            Self::PowerShell => quote_powershell(value),
+            Self::Nushell => quote_nushell(value),
+        }
+    }
+}
+
+impl Exporter for Shell {
+    fn export(&self, vars: &Scope) -> ExportResult<String> {
+        let mut out = String::with_capacity(vars.len() * 32);
+        for (name, value) in vars {
+            check_name(name)?;
+            let value = self.quote(value);
+            let _ = match self {
+                Self::Posix | Self::Bash | Self::Zsh => writeln!(out, "export {}={}", name, value),
+                Self::Fish => writeln!(out, "set -gx {} {}", name, value),
+                Self::PowerShell => writeln!(out, "$env:{} = {}", name, value),
+                Self::Nushell => writeln!(out, "$env.{} = {}", name, value),
+            };
+        }
+        Ok(out)
+    }
+}
+
+/// PowerShell treats typographic single quotes like ASCII ones,
+/// and all of them are escaped by doubling.
+fn quote_powershell(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('\'');
+    for ch in value.chars() {
+        if matches!(ch, '\'' | '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}') {
+            out.push(ch);
+        }
+        out.push(ch);
+    }
+    out.push('\'');
+    out
+}
+
+/// Nushell single-quoted strings cannot contain single quotes,
+/// so we use double-quoted strings and their escape sequences.
+fn quote_nushell(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{{{:x}}}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
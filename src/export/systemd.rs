@@ -0,0 +1,46 @@
+use std::fmt::Write;
+
+use super::{check_name, ExportError, ExportResult, Exporter};
+use crate::evaluator::Scope;
+
+/// Exports variables in the format read by the `EnvironmentFile=` directive of systemd units.
+///
+/// Values are double-quoted, so that whitespace and line breaks are preserved,
+/// and no expansion is performed by systemd.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EnvironmentFile;
+
+impl Exporter for EnvironmentFile {
+    fn export(&self, vars: &Scope) -> ExportResult<String> {
+        let mut out = String::with_capacity(vars.len() * 32);
+        for (name, value) in vars {
+            check_name(name)?;
+            // systemd rejects control characters other than tabs and line feeds.
+            if value
+                .chars()
+                .any(|c| c.is_control() && !matches!(c, '\t' | '\n'))
+            {
+                return Err(ExportError::UnsupportedValue(
+                    name.clone(),
+                    "control characters cannot be represented in a systemd EnvironmentFile".into(),
+                ));
+            }
+            let _ = writeln!(out, "{}={}", name, quote(value));
+        }
+        Ok(out)
+    }
+}
+
+/// Inside double quotes, systemd only unescapes the same characters as POSIX shells.
+fn quote(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for ch in value.chars() {
+        if matches!(ch, '"' | '\\' | '`' | '$') {
+            out.push('\\');
+        }
+        out.push(ch);
+    }
+    out.push('"');
+    out
+}
@@ -0,0 +1,201 @@
+use rstest::rstest;
+
+use super::{shell::Shell, ExportError, Exporter};
+use crate::evaluator::Scope;
+
+macro_rules! scope {
+    ($($k:literal: $v:literal),+) => {{
+       let mut scope = Scope::new();
+       $(scope.insert($k.to_string(), $v.to_string());)+
+       scope
+    }};
+}
+
+#[rstest]
+#[case::posix(Shell::Posix, "export A='it'\\''s'\nexport B='a\nb'\n")]
+#[case::bash(Shell::Bash, "export A='it'\\''s'\nexport B='a\nb'\n")]
+#[case::zsh(Shell::Zsh, "export A='it'\\''s'\nexport B='a\nb'\n")]
+#[case::fish(Shell::Fish, "set -gx A 'it\\'s'\nset -gx B 'a\nb'\n")]
+#[case::powershell(Shell::PowerShell, "$env:A = 'it''s'\n$env:B = 'a\nb'\n")]
+#[case::nushell(Shell::Nushell, "$env.A = \"it's\"\n$env.B = \"a\\nb\"\n")]
+fn test_shell_export(#[case] shell: Shell, #[case] expected: &str) {
+    let vars = scope!["A": "it's", "B": "a\nb"];
+    assert_eq!(expected, shell.export(&vars).unwrap());
+}
+
+#[rstest]
+#[case::posix(Shell::Posix, r#"'\ "$x" `y`'"#)]
+#[case::fish(Shell::Fish, r#"'\\ "$x" `y`'"#)]
+#[case::powershell(Shell::PowerShell, r#"'\ "$x" `y`'"#)]
+#[case::nushell(Shell::Nushell, r#""\\ \"$x\" `y`""#)]
+fn test_shell_quote(#[case] shell: Shell, #[case] expected: &str) {
+    assert_eq!(expected, shell.quote(r#"\ "$x" `y`"#));
+}
+
+#[test]
+fn test_powershell_typographic_quotes() {
+    assert_eq!(
+        "'a\u{2019}\u{2019}b'",
+        Shell::PowerShell.quote("a\u{2019}b")
+    );
+}
+
+#[test]
+fn test_nushell_control_characters() {
+    assert_eq!(r#""a\u{1b}b""#, Shell::Nushell.quote("a\x1bb"));
+}
+
+#[test]
+fn test_invalid_name() {
+    let vars = scope!["FOO.BAR": "baz"];
+    let err = Shell::Posix.export(&vars).unwrap_err();
+    assert!(matches!(err, ExportError::InvalidName(name) if name == "FOO.BAR"));
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_json() {
+    use super::json::Json;
+    let vars = scope!["B": "1", "A": "it's \"quoted\"\n"];
+    assert_eq!(
+        "{\"B\":\"1\",\"A\":\"it's \\\"quoted\\\"\\n\"}\n",
+        Json::new().export(&vars).unwrap()
+    );
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_json_nested() {
+    use super::json::Json;
+    let vars = scope!["DB__HOST": "localhost", "APP": "foo", "DB__PORT": "5432"];
+    assert_eq!(
+        "{\"DB\":{\"HOST\":\"localhost\",\"PORT\":\"5432\"},\"APP\":\"foo\"}\n",
+        Json::new().nested("__").export(&vars).unwrap()
+    );
+}
+
+#[cfg(feature = "json")]
+#[rstest]
+#[case::value_then_table(scope!["DB": "x", "DB__HOST": "localhost"])]
+#[case::table_then_value(scope!["DB__HOST": "localhost", "DB": "x"])]
+fn test_nesting_conflict(#[case] vars: Scope) {
+    use super::json::Json;
+    let err = Json::new().nested("__").export(&vars).unwrap_err();
+    assert!(matches!(err, ExportError::KeyConflict(_)));
+}
+
+#[cfg(feature = "json")]
+#[rstest]
+#[case::leading(scope!["__FOO": "x"])]
+#[case::trailing(scope!["FOO__": "x"])]
+#[case::inner(scope!["A____B": "x"])]
+fn test_nesting_empty_key(#[case] vars: Scope) {
+    use super::json::Json;
+    let err = Json::new().nested("__").export(&vars).unwrap_err();
+    assert!(matches!(err, ExportError::EmptyKey(_)));
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn test_yaml() {
+    use super::yaml::Yaml;
+    let vars = scope!["DB__HOST": "localhost", "APP": "true", "DB__PORT": "5432"];
+    assert_eq!(
+        "DB:\n  HOST: localhost\n  PORT: '5432'\nAPP: 'true'\n",
+        Yaml::new().nested("__").export(&vars).unwrap()
+    );
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn test_toml() {
+    use super::toml::Toml;
+    let vars = scope!["DB__HOST": "localhost", "APP": "a\nb", "DB__PORT": "5432"];
+    assert_eq!(
+        "APP = \"\"\"\na\nb\"\"\"\n\n[DB]\nHOST = \"localhost\"\nPORT = \"5432\"\n",
+        Toml::new().nested("__").export(&vars).unwrap()
+    );
+}
+
+#[test]
+fn test_docker_env_file() {
+    use super::docker::DockerEnvFile;
+    let vars = scope!["A": " it's \"verbatim\" ", "B": "$HOME"];
+    assert_eq!(
+        "A= it's \"verbatim\" \nB=$HOME\n",
+        DockerEnvFile.export(&vars).unwrap()
+    );
+    let vars = scope!["A": "a\nb"];
+    let err = DockerEnvFile.export(&vars).unwrap_err();
+    assert!(matches!(err, ExportError::UnsupportedValue(name, _) if name == "A"));
+}
+
+#[cfg(feature = "kubernetes")]
+#[test]
+fn test_configmap() {
+    use super::kubernetes::ConfigMap;
+    let vars = scope!["B": "1", "A": "a\nb"];
+    assert_eq!(
+        "apiVersion: v1\nkind: ConfigMap\nmetadata:\n  name: app\n  namespace: prod\ndata:\n  B: '1'\n  A: |-\n    a\n    b\n",
+        ConfigMap::new("app").namespace("prod").export(&vars).unwrap()
+    );
+}
+
+#[cfg(feature = "kubernetes")]
+#[test]
+fn test_secret() {
+    use super::kubernetes::Secret;
+    let vars = scope!["PASSWORD": "hunter2"];
+    assert_eq!(
+        "apiVersion: v1\nkind: Secret\nmetadata:\n  name: app\ntype: Opaque\ndata:\n  PASSWORD: aHVudGVyMg==\n",
+        Secret::new("app").export(&vars).unwrap()
+    );
+    let vars = scope!["NOT VALID": "x"];
+    let err = Secret::new("app").export(&vars).unwrap_err();
+    assert!(matches!(err, ExportError::InvalidName(_)));
+}
+
+#[test]
+fn test_systemd_environment_file() {
+    use super::systemd::EnvironmentFile;
+    let vars = scope!["A": "it's \"$quoted\" `here`", "B": "a\\\nb\t"];
+    assert_eq!(
+        "A=\"it's \\\"\\$quoted\\\" \\`here\\`\"\nB=\"a\\\\\nb\t\"\n",
+        EnvironmentFile.export(&vars).unwrap()
+    );
+    let vars = scope!["A": "a\rb"];
+    let err = EnvironmentFile.export(&vars).unwrap_err();
+    assert!(matches!(err, ExportError::UnsupportedValue(name, _) if name == "A"));
+}
+
+#[test]
+fn test_etc_environment() {
+    use super::pam::EtcEnvironment;
+    let vars = scope!["A": "\"quoted\"", "B": "", "C": "a \\ $b"];
+    assert_eq!(
+        "A=\"\"quoted\"\"\nB=\"\"\nC=\"a \\ $b\"\n",
+        EtcEnvironment.export(&vars).unwrap()
+    );
+    let vars = scope!["A": "a\nb"];
+    let err = EtcEnvironment.export(&vars).unwrap_err();
+    assert!(matches!(err, ExportError::UnsupportedValue(name, _) if name == "A"));
+    let vars = scope!["URL": "http://x/#frag"];
+    let err = EtcEnvironment.export(&vars).unwrap_err();
+    assert!(matches!(err, ExportError::UnsupportedValue(name, _) if name == "URL"));
+}
+
+#[test]
+fn test_github_actions() {
+    use super::github::GithubActions;
+    let vars = scope!["A": "a=b<<c", "B": "a\nb\n", "C": "EOF\nEOF_1\nEOF_3"];
+    assert_eq!(
+        "A=a=b<<c\nB<<EOF\na\nb\n\nEOF\nC<<EOF_2\nEOF\nEOF_1\nEOF_3\nEOF_2\n",
+        GithubActions.export(&vars).unwrap()
+    );
+    let vars = scope!["A": "a\r\nb"];
+    let err = GithubActions.export(&vars).unwrap_err();
+    assert!(matches!(err, ExportError::UnsupportedValue(name, _) if name == "A"));
+    let vars = scope!["A<<B": "c"];
+    let err = GithubActions.export(&vars).unwrap_err();
+    assert!(matches!(err, ExportError::InvalidName(_)));
+}
@@ -0,0 +1,30 @@
+use super::{tree::Node, ExportResult, Exporter};
+use crate::evaluator::Scope;
+
+/// Exports variables as a TOML document.
+///
+/// Since TOML requires it, nested tables are written after the other values.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Toml {
+    separator: Option<String>,
+}
+
+impl Toml {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Nests variables into tables by splitting their names on the given separator,
+    /// i.e. `DB__HOST` becomes `[DB] HOST = "..."` when using `__`.
+    pub fn nested(mut self, separator: impl Into<String>) -> Self {
+        self.separator = Some(separator.into());
+        self
+    }
+}
+
+impl Exporter for Toml {
+    fn export(&self, vars: &Scope) -> ExportResult<String> {
+        let tree = Node::new(vars, self.separator.as_deref())?;
+        Ok(::toml::to_string(&tree)?)
+    }
+}
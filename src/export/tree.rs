@@ -0,0 +1,63 @@
+use indexmap::{map::Entry, IndexMap};
+use serde::{ser::SerializeMap, Serialize, Serializer};
+
+use super::{ExportError, ExportResult};
+use crate::evaluator::Scope;
+
+/// A tree of variables, nested by splitting their names on a separator.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) enum Node<'a> {
+    Value(&'a str),
+    Table(IndexMap<&'a str, Node<'a>>),
+}
+
+impl<'a> Node<'a> {
+    pub fn new(vars: &'a Scope, separator: Option<&str>) -> ExportResult<Self> {
+        let mut root = IndexMap::with_capacity(vars.len());
+        for (name, value) in vars {
+            let mut keys: Vec<&str> = match separator {
+                Some(sep) if !sep.is_empty() => name.split(sep).collect(),
+                _ => vec![name],
+            };
+            if keys.iter().any(|key| key.is_empty()) {
+                return Err(ExportError::EmptyKey(name.clone()));
+            }
+            let leaf = keys.pop().unwrap();
+            let mut table = &mut root;
+            for key in keys {
+                let node = table
+                    .entry(key)
+                    .or_insert_with(|| Node::Table(IndexMap::new()));
+                match node {
+                    Node::Table(t) => table = t,
+                    Node::Value(_) => return Err(ExportError::KeyConflict(name.clone())),
+                }
+            }
+            match table.entry(leaf) {
+                Entry::Vacant(e) => {
+                    e.insert(Node::Value(value));
+                }
+                Entry::Occupied(_) => return Err(ExportError::KeyConflict(name.clone())),
+            }
+        }
+        Ok(Node::Table(root))
+    }
+}
+
+impl Serialize for Node<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Value(value) => serializer.serialize_str(value),
+            Self::Table(table) => {
+                let mut map = serializer.serialize_map(Some(table.len()))?;
+                for (key, node) in table {
+                    map.serialize_entry(key, node)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use super::{tree::Node, ExportResult, Exporter};
+use crate::evaluator::Scope;
+
+/// Exports variables as a YAML mapping.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Yaml {
+    separator: Option<String>,
+}
+
+impl Yaml {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Nests variables into mappings by splitting their names on the given separator,
+    /// i.e. `DB__HOST` becomes `DB: {HOST: ...}` when using `__`.
+    pub fn nested(mut self, separator: impl Into<String>) -> Self {
+        self.separator = Some(separator.into());
+        self
+    }
+}
+
+impl Exporter for Yaml {
+    fn export(&self, vars: &Scope) -> ExportResult<String> {
+        let tree = Node::new(vars, self.separator.as_deref())?;
+        Ok(serde_yaml::to_string(&tree)?)
+    }
+}
@@ -0,0 +1,192 @@
+//! Static analysis of the dependencies between variables.
+//!
+//! ```
+//! use potenv::{graph::DependencyGraph, parser::parse};
+//!
+//! let ast = parse("HOST=${HOST:-localhost}\nURL=http://${HOST}:${PORT:?}/", None).unwrap();
+//! let graph = DependencyGraph::new(&ast);
+//! let inputs: Vec<_> = graph.inputs().keys().collect();
+//! assert_eq!(vec!["HOST", "PORT"], inputs);
+//! assert!(graph.inputs()["PORT"].required);
+//! ```
+
+use indexmap::{IndexMap, IndexSet};
+
+use crate::parser::ast::{Assignment, Expression, Operator};
+
+#[cfg(test)]
+mod tests;
+
+/// A variable referenced by an assignment.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Dependency {
+    pub name: String,
+    /// Whether the variable is not defined by a previous assignment,
+    /// so that its value can only come from the environment.
+    pub external: bool,
+}
+
+/// A variable that the dotenv files expect from the environment.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Input {
+    /// Whether a reference fails when the variable is unset, i.e. `${NAME?}` or `${NAME:?}`.
+    pub required: bool,
+    /// The variables whose value depends on the input.
+    pub dependents: IndexSet<String>,
+}
+
+/// The dependencies between variables, as computed from their assignments.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DependencyGraph {
+    variables: IndexMap<String, IndexSet<Dependency>>,
+    inputs: IndexMap<String, Input>,
+}
+
+impl DependencyGraph {
+    /// Computes the dependency graph of the given assignments, in evaluation order.
+    pub fn new<'a, I>(assignments: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Assignment>,
+    {
+        let mut graph = Self::default();
+        for node in assignments {
+            let mut dependencies = IndexSet::new();
+            graph.visit(&node.name, &node.value, &mut dependencies);
+            graph
+                .variables
+                .entry(node.name.clone())
+                .or_default()
+                .extend(dependencies);
+        }
+        graph
+    }
+
+    /// Returns the variables defined by the assignments, along with their dependencies.
+    pub fn variables(&self) -> impl Iterator<Item = (&str, &IndexSet<Dependency>)> {
+        self.variables
+            .iter()
+            .map(|(name, deps)| (name.as_str(), deps))
+    }
+
+    /// Returns the dependencies of a variable, or `None` if no assignment defines it.
+    pub fn dependencies(&self, name: &str) -> Option<&IndexSet<Dependency>> {
+        self.variables.get(name)
+    }
+
+    /// Returns the variables that are referenced before being defined,
+    /// in order of first reference.
+    pub fn inputs(&self) -> &IndexMap<String, Input> {
+        &self.inputs
+    }
+
+    /// Renders the graph in the DOT language of Graphviz.
+    ///
+    /// Inputs are rendered as dashed boxes, whose identifiers are prefixed by `env:`.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph dotenv {\n");
+        for name in self.variables.keys() {
+            out.push_str(&format!("    {};\n", quote(name)));
+        }
+        for name in self.inputs.keys() {
+            out.push_str(&format!(
+                "    {} [label={}, shape=box, style=dashed];\n",
+                quote(&format!("env:{name}")),
+                quote(name)
+            ));
+        }
+        for (name, dependencies) in &self.variables {
+            for dependency in dependencies {
+                let target = match dependency.external {
+                    true => format!("env:{}", dependency.name),
+                    false => dependency.name.clone(),
+                };
+                out.push_str(&format!("    {} -> {};\n", quote(name), quote(&target)));
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    fn visit(&mut self, dependent: &str, value: &[Expression], deps: &mut IndexSet<Dependency>) {
+        for expr in value {
+            let expansion = match expr {
+                Expression::Characters(_) => continue,
+                Expression::Length(node) => {
+                    self.visit_reference(dependent, &node.name, false, deps);
+                    continue;
+                }
+                Expression::Substring(node) => {
+                    self.visit_reference(dependent, &node.name, false, deps);
+                    continue;
+                }
+                Expression::Filtered(node) => {
+                    self.visit_reference(dependent, &node.name, false, deps);
+                    continue;
+                }
+                Expression::Resolved(node) => {
+                    self.visit(dependent, &node.argument, deps);
+                    continue;
+                }
+                Expression::Tilde(node) => {
+                    if node.user.is_empty() {
+                        self.visit_reference(dependent, "HOME", false, deps);
+                    }
+                    continue;
+                }
+                Expression::Replacement(node) => {
+                    self.visit_reference(dependent, &node.name, false, deps);
+                    self.visit(dependent, &node.pattern, deps);
+                    self.visit(dependent, &node.replacement, deps);
+                    continue;
+                }
+                Expression::Quoted(node) => {
+                    self.visit(dependent, std::slice::from_ref(node), deps);
+                    continue;
+                }
+                Expression::Expansion(expansion) => expansion,
+            };
+            let name = &expansion.name;
+            let required = matches!(
+                expansion.operator,
+                Operator::ErrorIfUnset | Operator::ErrorIfUnsetOrNull
+            );
+            self.visit_reference(dependent, name, required, deps);
+            self.visit(dependent, &expansion.rhs, deps);
+            if matches!(
+                expansion.operator,
+                Operator::AssignIfUnset | Operator::AssignIfUnsetOrNull
+            ) {
+                let mut assigned = IndexSet::new();
+                self.visit(name, &expansion.rhs, &mut assigned);
+                self.variables
+                    .entry(name.clone())
+                    .or_default()
+                    .extend(assigned);
+            }
+        }
+    }
+
+    fn visit_reference(
+        &mut self,
+        dependent: &str,
+        name: &str,
+        required: bool,
+        deps: &mut IndexSet<Dependency>,
+    ) {
+        let external = !self.variables.contains_key(name);
+        deps.insert(Dependency {
+            name: name.to_owned(),
+            external,
+        });
+        if external {
+            let input = self.inputs.entry(name.to_owned()).or_default();
+            input.required |= required;
+            input.dependents.insert(dependent.to_owned());
+        }
+    }
+}
+
+/// Quotes an identifier of the DOT language.
+fn quote(id: &str) -> String {
+    format!("\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,74 @@
+use rstest::rstest;
+
+use super::{Dependency, DependencyGraph};
+use crate::{options::Options, parser::parse_with_options};
+
+fn graph(input: &str) -> DependencyGraph {
+    let options = Options {
+        string_length: true,
+        bash_expansions: true,
+        filters: true,
+        resolvers: true,
+        tilde_expansion: true,
+        ..Default::default()
+    };
+    DependencyGraph::new(&parse_with_options(input, None, options).unwrap())
+}
+
+fn dep(name: &str, external: bool) -> Dependency {
+    Dependency {
+        name: name.to_owned(),
+        external,
+    }
+}
+
+#[rstest]
+#[case::no_dependencies("A=1", "A", vec![])]
+#[case::internal("A=1\nB=$A", "B", vec![dep("A", false)])]
+#[case::external("A=$HOME/bin", "A", vec![dep("HOME", true)])]
+#[case::self_reference("PATH=$PATH:/bin", "PATH", vec![dep("PATH", true)])]
+#[case::redefinition("A=1\nA=${A}2", "A", vec![dep("A", false)])]
+#[case::string_length("A=${#B}", "A", vec![dep("B", true)])]
+#[case::substring("A=${B:1:2}", "A", vec![dep("B", true)])]
+#[case::replacement("A=${B/$C/$D}", "A", vec![dep("B", true), dep("C", true), dep("D", true)])]
+#[case::filtered("A=${B|split|first}", "A", vec![dep("B", true)])]
+#[case::resolved("A=${file:$B/db}", "A", vec![dep("B", true)])]
+#[case::tilde("A=~/bin:~user/bin", "A", vec![dep("HOME", true)])]
+#[case::nested("A=${B:-${C}}", "A", vec![dep("B", true), dep("C", true)])]
+#[case::assigning_expansion("A=${B:=$C}\nD=$B", "B", vec![dep("C", true)])]
+#[case::after_assigning_expansion("A=${B:=$C}\nD=$B", "D", vec![dep("B", false)])]
+fn test_dependencies(#[case] input: &str, #[case] name: &str, #[case] expected: Vec<Dependency>) {
+    let graph = graph(input);
+    let deps: Vec<_> = graph.dependencies(name).unwrap().iter().cloned().collect();
+    assert_eq!(expected, deps);
+}
+
+#[test]
+fn test_inputs() {
+    let graph = graph("A=$HOME/${DIR:?}\nB=${HOME}\nC=$A");
+    let inputs: Vec<_> = graph
+        .inputs()
+        .iter()
+        .map(|(name, input)| {
+            let dependents: Vec<_> = input.dependents.iter().map(String::as_str).collect();
+            (name.as_str(), input.required, dependents)
+        })
+        .collect();
+    assert_eq!(
+        vec![("HOME", false, vec!["A", "B"]), ("DIR", true, vec!["A"])],
+        inputs
+    );
+}
+
+#[test]
+fn test_to_dot() {
+    let expected = r#"digraph dotenv {
+    "A";
+    "B";
+    "env:HOME" [label="HOME", shape=box, style=dashed];
+    "A" -> "env:HOME";
+    "B" -> "A";
+}
+"#;
+    assert_eq!(expected, graph("A=$HOME\nB=${A}").to_dot());
+}
@@ -0,0 +1,232 @@
+//! Classifies the text of dotenv files for syntax highlighting,
+//! exactly as the tokenizer understands it.
+//!
+//! ```
+//! use potenv::highlight::{highlight, Highlight};
+//!
+//! let input = "# comment\nURL=\"http://${HOST:-localhost}\"";
+//! let spans: Vec<_> = highlight(input)
+//!     .map(|span| (span.kind, &input[span.range]))
+//!     .collect();
+//! assert_eq!(
+//!     vec![
+//!         (Highlight::Comment, "# comment"),
+//!         (Highlight::Whitespace, "\n"),
+//!         (Highlight::Name, "URL"),
+//!         (Highlight::Operator, "="),
+//!         (Highlight::String, "\"http://"),
+//!         (Highlight::Expansion, "${HOST"),
+//!         (Highlight::Operator, ":-"),
+//!         (Highlight::Text, "localhost"),
+//!         (Highlight::Expansion, "}"),
+//!         (Highlight::String, "\""),
+//!     ],
+//!     spans
+//! );
+//! ```
+
+use std::ops::Range;
+
+use crate::{
+    options::Options,
+    parser::{require_posix, ParseResult},
+    tokenizer::{is_blank, is_dq_escape, is_wsnl, pos::Position, State, Tokenizer, TraceEntry},
+};
+
+#[cfg(test)]
+mod tests;
+
+/// The syntactic class of a span of text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Highlight {
+    /// Blanks and newlines separating assignments.
+    Whitespace,
+    /// A comment, from `#` to the end of the line.
+    Comment,
+    /// The `export` keyword, when enabled in the options.
+    Keyword,
+    /// The name of an assignment.
+    Name,
+    /// The `=` of an assignment, or the operator of an expansion.
+    Operator,
+    /// Unquoted characters of a value.
+    Text,
+    /// A quoted string, including its quotes.
+    String,
+    /// A backslash escape sequence.
+    Escape,
+    /// A parameter expansion, except for its operator and default value.
+    Expansion,
+    /// The name of a filter applied to an expansion.
+    Filter,
+    /// The text starting at a syntax error.
+    Invalid,
+}
+
+/// A span of text of a single [Highlight] class.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub kind: Highlight,
+    /// The byte range of the span in the input.
+    pub range: Range<usize>,
+}
+
+/// Iterator over the spans of a dotenv file, covering every byte of the input.
+#[derive(Debug, Clone)]
+pub struct Highlights {
+    /// The class of every character before `end`, by byte offset.
+    classes: Vec<(usize, Highlight)>,
+    index: usize,
+    /// The offset of the first invalid character, if any.
+    end: usize,
+    len: usize,
+}
+
+impl Iterator for Highlights {
+    type Item = Span;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Some(&(start, kind)) = self.classes.get(self.index) else {
+            if self.end < self.len {
+                let range = self.end..self.len;
+                self.end = self.len;
+                return Some(Span {
+                    kind: Highlight::Invalid,
+                    range,
+                });
+            }
+            return None;
+        };
+        self.index += self.classes[self.index..]
+            .iter()
+            .take_while(|(_, k)| *k == kind)
+            .count();
+        let end = self
+            .classes
+            .get(self.index)
+            .map_or(self.end, |(offset, _)| *offset);
+        Some(Span {
+            kind,
+            range: start..end,
+        })
+    }
+}
+
+/// Highlights a dotenv file following the specification.
+pub fn highlight(input: &str) -> Highlights {
+    highlight_posix(input, Options::default())
+}
+
+/// Highlights a dotenv file, accepting the syntax enabled in the options.
+/// Fails for dialects other than [crate::dialect::Dialect::Posix].
+pub fn highlight_with_options(input: &str, options: Options) -> ParseResult<Highlights> {
+    require_posix(options)?;
+    Ok(highlight_posix(input, options))
+}
+
+fn highlight_posix(input: &str, options: Options) -> Highlights {
+    let mut tokenizer = Tokenizer::with_options(input.chars(), None, options).traced();
+    let error = tokenizer.by_ref().find_map(Result::err);
+    let trace = tokenizer.take_trace();
+    let end = error.map_or(input.len(), |err| {
+        Position::new(err.line(), err.column()).offset(input)
+    });
+    let mut classes: Vec<(usize, Highlight)> = Vec::with_capacity(trace.len());
+    // The number of nested double-quoted strings.
+    let mut quoting = 0;
+    for (i, entry) in trace.iter().enumerate() {
+        if entry.offset >= end {
+            break;
+        }
+        let next = trace.get(i + 1);
+        let kind = classify(entry, next, quoting > 0);
+        match (entry.state, entry.ch) {
+            (State::AssignmentValue | State::ExpansionValue, '"') => quoting += 1,
+            (State::DoubleQuoted, '"') => quoting -= 1,
+            (State::AssignmentName, ch) if is_blank(ch) => {
+                // The name turns out to be the `export` keyword.
+                classes
+                    .iter_mut()
+                    .rev()
+                    .take_while(|(_, kind)| *kind == Highlight::Name)
+                    .for_each(|(_, kind)| *kind = Highlight::Keyword);
+            }
+            _ => (),
+        }
+        classes.push((entry.offset, kind));
+    }
+    Highlights {
+        classes,
+        index: 0,
+        end,
+        len: input.len(),
+    }
+}
+
+/// Classifies a character from the state of the tokenizer that consumed it.
+fn classify(entry: &TraceEntry, next: Option<&TraceEntry>, quoted: bool) -> Highlight {
+    let next_state = next.map(|entry| entry.state);
+    // Whether a backslash before the next character is kept in the value.
+    let literal_backslash =
+        next.is_none_or(|entry| quoted && entry.ch != '\n' && !is_dq_escape(entry.ch));
+    match (entry.state, entry.ch) {
+        (State::AssignmentList | State::ExportPrefix, '#') => Highlight::Comment,
+        (State::AssignmentList | State::ExportPrefix, ch) if is_wsnl(ch) => Highlight::Whitespace,
+        (State::AssignmentList | State::ExportPrefix, _) => Highlight::Name,
+        (State::Comment, '\n') => Highlight::Whitespace,
+        (State::Comment, _) => Highlight::Comment,
+        (State::AssignmentName, '=') => Highlight::Operator,
+        (State::AssignmentName, ch) if is_blank(ch) => Highlight::Whitespace,
+        (State::AssignmentName, _) => Highlight::Name,
+        (State::AssignmentValue, ch) if is_wsnl(ch) => Highlight::Whitespace,
+        (State::AssignmentValue, '\\') | (State::AssignmentValueEscape, _) => Highlight::Escape,
+        (State::AssignmentValue, '\'' | '"') => Highlight::String,
+        (
+            State::AssignmentValue | State::Template | State::DoubleQuoted | State::ExpansionValue,
+            '$',
+        ) if next_state == Some(State::Dollar) => Highlight::Expansion,
+        (State::AssignmentValue | State::Template, _) => Highlight::Text,
+        (State::SingleQuoted, _) => Highlight::String,
+        (State::DoubleQuoted, '\\') if !literal_backslash => Highlight::Escape,
+        (State::DoubleQuotedEscape, ch) if ch == '\n' || is_dq_escape(ch) => Highlight::Escape,
+        (State::DoubleQuoted | State::DoubleQuotedEscape, _) => Highlight::String,
+        (State::ComplexExpansion, ':' | '-' | '=' | '+' | '?' | '#' | '%' | '/' | '|')
+        | (State::SubstringOffset | State::FilterName, ':')
+        | (State::FilterName | State::FilterArgument, '|')
+        | (
+            State::ExpansionOperator
+            | State::PatternOperator
+            | State::ReplacementOperator
+            | State::ReplacementSeparator,
+            _,
+        ) => Highlight::Operator,
+        (State::SubstringOffset | State::SubstringLength, '}')
+        | (State::FilterName | State::FilterArgument, '}') => Highlight::Expansion,
+        (State::FilterName, _) => Highlight::Filter,
+        (State::TildePrefix, _) => Highlight::Expansion,
+        (State::FilterArgument, _) => Highlight::Text,
+        (State::SubstringOffset | State::SubstringLength, ch) if is_blank(ch) => {
+            Highlight::Whitespace
+        }
+        (State::SubstringOffset | State::SubstringLength, _) => Highlight::Text,
+        (
+            State::Dollar
+            | State::SimpleExpansion
+            | State::ComplexExpansionStart
+            | State::StringLengthStart
+            | State::StringLength
+            | State::ComplexExpansion,
+            _,
+        ) => Highlight::Expansion,
+        (State::ExpansionValue, '}') => Highlight::Expansion,
+        (State::ExpansionValue, '\\') if !literal_backslash => Highlight::Escape,
+        (State::ExpansionValueEscape, ch) if !quoted || ch == '\n' || is_dq_escape(ch) => {
+            Highlight::Escape
+        }
+        (State::ExpansionValue, '"') => Highlight::String,
+        (State::ExpansionValue, '\'') if next_state == Some(State::SingleQuoted) => {
+            Highlight::String
+        }
+        (State::ExpansionValue | State::ExpansionValueEscape, _) => Highlight::Text,
+    }
+}
@@ -0,0 +1,181 @@
+use rstest::rstest;
+
+use super::{highlight, highlight_with_options, Highlight};
+use crate::{dialect::Dialect, options::Options, parser::ParseError};
+
+use Highlight::*;
+
+fn spans(input: &str, options: Options) -> Vec<(Highlight, &str)> {
+    highlight_with_options(input, options)
+        .unwrap()
+        .map(|span| (span.kind, &input[span.range]))
+        .collect()
+}
+
+#[rstest]
+#[case::empty("", vec![])]
+#[case::comment("# a\n  #b", vec![(Comment, "# a"), (Whitespace, "\n  "), (Comment, "#b")])]
+#[case::unquoted("A=a b=c", vec![(Name, "A"), (Operator, "="), (Text, "a"), (Whitespace, " "), (Name, "b"), (Operator, "="), (Text, "c")])]
+#[case::escape("A=a\\ b", vec![(Name, "A"), (Operator, "="), (Text, "a"), (Escape, "\\ "), (Text, "b")])]
+#[case::single_quoted("A='$a\\'", vec![(Name, "A"), (Operator, "="), (String, "'$a\\'")])]
+#[case::double_quoted_escapes("A=\"\\$\\a\"", vec![(Name, "A"), (Operator, "="), (String, "\""), (Escape, "\\$"), (String, "\\a\"")])]
+#[case::simple_expansion("A=$B-$", vec![(Name, "A"), (Operator, "="), (Expansion, "$B"), (Text, "-$")])]
+#[case::quoted_expansion("A=\"$B $\"", vec![(Name, "A"), (Operator, "="), (String, "\""), (Expansion, "$B"), (String, " $\"")])]
+#[case::complex_expansion("A=${B}", vec![(Name, "A"), (Operator, "="), (Expansion, "${B}")])]
+#[case::expansion_operator("A=${B?}", vec![(Name, "A"), (Operator, "="), (Expansion, "${B"), (Operator, "?"), (Expansion, "}")])]
+#[case::expansion_value("A=${B:-'x'\\}$C}", vec![(Name, "A"), (Operator, "="), (Expansion, "${B"), (Operator, ":-"), (String, "'x'"), (Escape, "\\}"), (Expansion, "$C}")])]
+#[case::quoted_expansion_value("A=\"${B:-'\\x}\"", vec![(Name, "A"), (Operator, "="), (String, "\""), (Expansion, "${B"), (Operator, ":-"), (Text, "'\\x"), (Expansion, "}"), (String, "\"")])]
+#[case::invalid_character("A=1 B|C=2\nD=3", vec![(Name, "A"), (Operator, "="), (Text, "1"), (Whitespace, " "), (Name, "B"), (Invalid, "|C=2\nD=3")])]
+#[case::unterminated_string("A=1\nB='2\nC=3", vec![(Name, "A"), (Operator, "="), (Text, "1"), (Whitespace, "\n"), (Name, "B"), (Operator, "="), (Invalid, "'2\nC=3")])]
+#[case::unsupported_parameter("A=$1", vec![(Name, "A"), (Operator, "="), (Expansion, "$"), (Invalid, "1")])]
+#[case::export_disabled("export A=1", vec![(Name, "export"), (Invalid, " A=1")])]
+fn test_highlight(#[case] input: &str, #[case] expected: Vec<(Highlight, &str)>) {
+    assert_eq!(expected, spans(input, Options::default()));
+}
+
+#[test]
+fn test_export_prefix() {
+    let options = Options {
+        export_prefix: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        vec![
+            (Keyword, "export"),
+            (Whitespace, "  "),
+            (Name, "A"),
+            (Operator, "="),
+            (Text, "1")
+        ],
+        spans("export  A=1", options)
+    );
+}
+
+#[test]
+fn test_pattern_removal() {
+    let options = Options {
+        pattern_removal: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        vec![
+            (Name, "A"),
+            (Operator, "="),
+            (Expansion, "${B"),
+            (Operator, "##"),
+            (Text, "*/"),
+            (Expansion, "}${C"),
+            (Operator, "%"),
+            (String, "'/'"),
+            (Expansion, "}"),
+        ],
+        spans("A=${B##*/}${C%'/'}", options)
+    );
+}
+
+#[test]
+fn test_bash_expansions() {
+    let options = Options {
+        bash_expansions: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        vec![
+            (Name, "A"),
+            (Operator, "="),
+            (Expansion, "${B"),
+            (Operator, ":"),
+            (Whitespace, " "),
+            (Text, "-1"),
+            (Operator, ":"),
+            (Text, "2"),
+            (Expansion, "}${C"),
+            (Operator, "//"),
+            (Text, "x"),
+            (Operator, "/"),
+            (Text, "y"),
+            (Expansion, "}${D"),
+            (Operator, "/"),
+            (Text, "z"),
+            (Expansion, "}"),
+        ],
+        spans("A=${B: -1:2}${C//x/y}${D/z}", options)
+    );
+}
+
+#[test]
+fn test_filters() {
+    let options = Options {
+        filters: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        vec![
+            (Name, "A"),
+            (Operator, "="),
+            (Expansion, "${B"),
+            (Operator, "|"),
+            (Filter, "split"),
+            (Operator, ":"),
+            (Text, ","),
+            (Operator, "|"),
+            (Filter, "first"),
+            (Expansion, "}"),
+        ],
+        spans("A=${B|split:,|first}", options)
+    );
+}
+
+#[test]
+fn test_tilde_expansion() {
+    let options = Options {
+        tilde_expansion: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        vec![
+            (Name, "A"),
+            (Operator, "="),
+            (Expansion, "~user"),
+            (Text, "/bin:"),
+            (Expansion, "~"),
+        ],
+        spans("A=~user/bin:~", options)
+    );
+}
+
+#[test]
+fn test_string_length() {
+    let options = Options {
+        string_length: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        vec![(Name, "A"), (Operator, "="), (Expansion, "${#B}")],
+        spans("A=${#B}", options)
+    );
+}
+
+#[test]
+fn test_unsupported_dialect() {
+    let options = Options {
+        dialect: Dialect::Ruby,
+        ..Options::default()
+    };
+    let err = highlight_with_options("A=1", options).unwrap_err();
+    assert_eq!(ParseError::UnsupportedDialect(Dialect::Ruby), err);
+}
+
+#[rstest]
+#[case("A=é\\\nb # c\nB=\"${C:=\"d\"}\"")]
+#[case("A=${B:-\"${C}'\\\"\"}'x'\nB=")]
+#[case("A=\"unterminated")]
+fn test_spans_cover_input(#[case] input: &str) {
+    let mut end = 0;
+    for span in highlight(input) {
+        assert_eq!(end, span.range.start, "{span:?}");
+        assert!(span.range.start < span.range.end, "{span:?}");
+        end = span.range.end;
+    }
+    assert_eq!(input.len(), end);
+}
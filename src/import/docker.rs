@@ -0,0 +1,43 @@
+//! Imports variables from files in the format of `docker run --env-file`.
+
+use super::{ImportError, ImportResult};
+use crate::{
+    parser::ast::{Assignment, Expansion, Expression},
+    tokenizer::pos::Position,
+};
+
+/// Parses a Docker env-file.
+///
+/// Values are taken verbatim, without quote removal nor expansion.
+/// Variables listed without a value are passed through from the environment,
+/// and evaluate to an empty string if the environment does not define them.
+pub fn parse(input: &str) -> ImportResult<Vec<Assignment>> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let mut nodes = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line[..line.len() - trimmed.len()].chars().count();
+        let position = Position::new(i + 1, indent + 1);
+        let line = trimmed;
+        let (name, value) = match line.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (line, None),
+        };
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(ImportError::InvalidName(name.to_owned(), i + 1));
+        }
+        let value = match value {
+            Some("") => vec![],
+            Some(value) => vec![Expression::Characters(value.to_owned())],
+            None => {
+                let expansion = Expansion::plain(name.to_owned()).at(position);
+                vec![Expression::Expansion(expansion)]
+            }
+        };
+        nodes.push(Assignment::new(name.to_owned(), value).at(position));
+    }
+    Ok(nodes)
+}
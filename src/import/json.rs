@@ -0,0 +1,28 @@
+//! Imports variables from JSON objects.
+
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use super::{ImportError, ImportResult};
+use crate::parser::ast::{Assignment, Expression};
+
+/// Parses a JSON object mapping variable names to strings, numbers or booleans.
+///
+/// Values are taken verbatim, without expansion.
+pub fn parse(input: &str) -> ImportResult<Vec<Assignment>> {
+    let map: IndexMap<String, Value> = serde_json::from_str(input)?;
+    map.into_iter()
+        .map(|(name, value)| {
+            let value = match value {
+                Value::String(s) if s.is_empty() => return Ok(Assignment::new(name, vec![])),
+                Value::String(s) => s,
+                Value::Number(n) => n.to_string(),
+                Value::Bool(b) => b.to_string(),
+                Value::Null | Value::Array(_) | Value::Object(_) => {
+                    return Err(ImportError::UnsupportedValue(name))
+                }
+            };
+            Ok(Assignment::new(name, vec![Expression::Characters(value)]))
+        })
+        .collect()
+}
@@ -0,0 +1,35 @@
+//! Importers converting variables from other formats into dotenv assignments,
+//! so that they can be evaluated alongside dotenv files.
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use potenv::{evaluator::Evaluator, import, parser::parse};
+//!
+//! let env = HashMap::new();
+//! let mut evaluator = Evaluator::new(&env, false);
+//! evaluator.evaluate(import::docker::parse("HOST=localhost\n").unwrap()).unwrap();
+//! evaluator.evaluate(parse("URL=http://${HOST}", None).unwrap()).unwrap();
+//! assert_eq!("http://localhost", evaluator.into_scope()["URL"]);
+//! ```
+
+use thiserror::Error;
+
+pub mod docker;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod systemd;
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error("Invalid variable name '{0}' on line {1}")]
+    InvalidName(String, usize),
+    #[error("Unsupported value for variable '{0}'")]
+    UnsupportedValue(String),
+    #[cfg(feature = "json")]
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+pub type ImportResult<T> = Result<T, ImportError>;
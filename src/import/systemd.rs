@@ -0,0 +1,201 @@
+//! Imports variables from files in the format read by the `EnvironmentFile=` directive
+//! of systemd units.
+
+use super::{ImportError, ImportResult};
+use crate::{
+    parser::ast::{Assignment, Expression},
+    tokenizer::{is_identifier, pos::Position},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    PreKey,
+    Key,
+    PreValue,
+    Value,
+    ValueEscape,
+    SingleQuoted,
+    DoubleQuoted,
+    DoubleQuotedEscape,
+    Comment,
+    CommentEscape,
+}
+
+#[inline(always)]
+fn is_newline(ch: char) -> bool {
+    matches!(ch, '\n' | '\r')
+}
+
+#[inline(always)]
+fn is_whitespace(ch: char) -> bool {
+    matches!(ch, ' ' | '\t' | '\n' | '\r')
+}
+
+/// Parses a systemd EnvironmentFile, following the quoting rules of systemd.
+///
+/// Values are taken verbatim after quote removal, since systemd performs no expansion.
+pub fn parse(input: &str) -> ImportResult<Vec<Assignment>> {
+    let mut parser = Parser::default();
+    for ch in input.chars() {
+        parser.consume(ch)?;
+    }
+    parser.finish()
+}
+
+#[derive(Debug)]
+struct Parser {
+    state: State,
+    line: usize,
+    column: usize,
+    key_position: Position,
+    key: String,
+    value: String,
+    // Length of the value before trailing unquoted whitespace.
+    value_len: usize,
+    nodes: Vec<Assignment>,
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Self {
+            state: State::PreKey,
+            line: 1,
+            column: 1,
+            key_position: Position::new(1, 1),
+            key: String::new(),
+            value: String::new(),
+            value_len: 0,
+            nodes: Vec::new(),
+        }
+    }
+}
+
+impl Parser {
+    fn consume(&mut self, ch: char) -> ImportResult<()> {
+        match self.state {
+            State::PreKey => match ch {
+                '#' | ';' => self.state = State::Comment,
+                c if is_whitespace(c) => (),
+                c => {
+                    self.key_position = Position::new(self.line, self.column);
+                    self.key.push(c);
+                    self.state = State::Key;
+                }
+            },
+            State::Key => match ch {
+                // A key without a value is ignored.
+                c if is_newline(c) => {
+                    self.key.clear();
+                    self.state = State::PreKey;
+                }
+                '=' => self.state = State::PreValue,
+                c => self.key.push(c),
+            },
+            State::PreValue => match ch {
+                c if is_newline(c) => {
+                    self.push()?;
+                    self.state = State::PreKey;
+                }
+                '\'' => self.state = State::SingleQuoted,
+                '"' => self.state = State::DoubleQuoted,
+                '\\' => self.state = State::ValueEscape,
+                c if is_whitespace(c) => (),
+                c => {
+                    self.push_value(c);
+                    self.state = State::Value;
+                }
+            },
+            State::Value => match ch {
+                c if is_newline(c) => {
+                    self.value.truncate(self.value_len);
+                    self.push()?;
+                    self.state = State::PreKey;
+                }
+                '\\' => self.state = State::ValueEscape,
+                c if is_whitespace(c) => self.value.push(c),
+                c => self.push_value(c),
+            },
+            State::ValueEscape => {
+                // An escaped line break continues the value on the next line.
+                if !is_newline(ch) {
+                    self.push_value(ch);
+                }
+                self.state = State::Value;
+            }
+            State::SingleQuoted => match ch {
+                '\'' => self.state = State::PreValue,
+                c => self.push_value(c),
+            },
+            State::DoubleQuoted => match ch {
+                '"' => self.state = State::PreValue,
+                '\\' => self.state = State::DoubleQuotedEscape,
+                c => self.push_value(c),
+            },
+            State::DoubleQuotedEscape => {
+                match ch {
+                    '"' | '\\' | '`' | '$' => self.push_value(ch),
+                    '\n' => (),
+                    c => {
+                        self.push_value('\\');
+                        self.push_value(c);
+                    }
+                }
+                self.state = State::DoubleQuoted;
+            }
+            State::Comment => match ch {
+                '\\' => self.state = State::CommentEscape,
+                c if is_newline(c) => self.state = State::PreKey,
+                _ => (),
+            },
+            State::CommentEscape => self.state = State::Comment,
+        }
+        if ch == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> ImportResult<Vec<Assignment>> {
+        match self.state {
+            State::Value | State::ValueEscape => {
+                self.value.truncate(self.value_len);
+                self.push()?;
+            }
+            State::PreValue
+            | State::SingleQuoted
+            | State::DoubleQuoted
+            | State::DoubleQuotedEscape => self.push()?,
+            _ => (),
+        }
+        Ok(self.nodes)
+    }
+
+    fn push_value(&mut self, ch: char) {
+        self.value.push(ch);
+        self.value_len = self.value.len();
+    }
+
+    fn push(&mut self) -> ImportResult<()> {
+        let name = self.key.trim_end();
+        if !is_identifier(name) {
+            return Err(ImportError::InvalidName(
+                name.to_owned(),
+                self.key_position.line,
+            ));
+        }
+        let value = if self.value.is_empty() {
+            vec![]
+        } else {
+            vec![Expression::Characters(self.value.clone())]
+        };
+        let assignment = Assignment::new(name.to_owned(), value).at(self.key_position);
+        self.nodes.push(assignment);
+        self.key.clear();
+        self.value.clear();
+        self.value_len = 0;
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use rstest::rstest;
+
+use super::{docker, systemd, ImportError};
+use crate::{
+    parser::ast::{Assignment, Expansion, Expression},
+    tokenizer::pos::Position,
+};
+
+fn assign(name: &str, value: &str) -> Assignment {
+    let value = match value {
+        "" => vec![],
+        v => vec![Expression::Characters(v.to_owned())],
+    };
+    Assignment::new(name.to_owned(), value)
+}
+
+#[test]
+fn test_docker() {
+    let input = "\u{feff}# comment\n\n  A= 'not quoted' $HOME \r\nB=\nC=a=b\nD\n";
+    let expansion = Expansion::plain("D".to_owned()).at(Position::new(6, 1));
+    let expected = vec![
+        assign("A", " 'not quoted' $HOME ").at(Position::new(3, 3)),
+        assign("B", "").at(Position::new(4, 1)),
+        assign("C", "a=b").at(Position::new(5, 1)),
+        Assignment::new("D".to_owned(), vec![Expression::Expansion(expansion)])
+            .at(Position::new(6, 1)),
+    ];
+    assert_eq!(expected, docker::parse(input).unwrap());
+}
+
+#[test]
+fn test_docker_invalid_name() {
+    let err = docker::parse("A=1\nB C=2").unwrap_err();
+    assert!(matches!(err, ImportError::InvalidName(name, 2) if name == "B C"));
+}
+
+#[rstest]
+#[case::unquoted("A=foo bar  ", "foo bar")]
+#[case::whitespace_around_equals("A = foo", "foo")]
+#[case::empty("A=", "")]
+#[case::single_quoted("A='it\\'s'", "it\\s'")]
+#[case::double_quoted(r#"A="a \"b\" \$c \n""#, r#"a "b" $c \n"#)]
+#[case::mixed(r#"A="a b"'c d'e"#, "a bc de")]
+#[case::quoted_trailing_whitespace("A=' a '  ", " a ")]
+#[case::multiline_quoted("A=\"a\nb\"", "a\nb")]
+#[case::line_continuation("A=a\\\nb", "ab")]
+#[case::unquoted_escape("A=\\$a\\ b", "$a b")]
+#[case::unterminated_quote("A=\"abc", "abc")]
+fn test_systemd_value(#[case] input: &str, #[case] expected: &str) {
+    let expected = assign("A", expected).at(Position::new(1, 1));
+    assert_eq!(vec![expected], systemd::parse(input).unwrap());
+}
+
+#[test]
+fn test_systemd() {
+    let input = "# comment \\\ncontinued\n; another comment\n\nA=1\nIGNORED\n  B=2";
+    let expected = vec![
+        assign("A", "1").at(Position::new(5, 1)),
+        assign("B", "2").at(Position::new(7, 3)),
+    ];
+    assert_eq!(expected, systemd::parse(input).unwrap());
+}
+
+#[test]
+fn test_systemd_invalid_name() {
+    let err = systemd::parse("A=1\n\nB.C=2").unwrap_err();
+    assert!(matches!(err, ImportError::InvalidName(name, 3) if name == "B.C"));
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_json() {
+    use super::json;
+    let input = r#"{"B": "${NOT_EXPANDED}", "A": 42, "C": true, "D": ""}"#;
+    let expected = vec![
+        assign("B", "${NOT_EXPANDED}"),
+        assign("A", "42"),
+        assign("C", "true"),
+        assign("D", ""),
+    ];
+    assert_eq!(expected, json::parse(input).unwrap());
+    let err = json::parse(r#"{"A": [1]}"#).unwrap_err();
+    assert!(matches!(err, ImportError::UnsupportedValue(name) if name == "A"));
+}
@@ -1,18 +1,43 @@
 #![doc = include_str!("../README.md")]
 
-use std::path::Path;
+use std::{ffi::OsStr, path::Path, process::Command};
 
+use dialect::Dialect;
 use env::{EnvProvider, ProcessEnvProvider};
-use evaluator::{EvaluationError, Evaluator, Scope};
-use parser::{parse, ParseError};
+use evaluator::{
+    filter::{Filters, Value},
+    resolver::Resolvers,
+    EvaluationError, Evaluator, Scope,
+};
+use graph::DependencyGraph;
+use lint::{Diagnostic, Linter};
+use options::Options;
+use parser::{ast::Assignment, parse_value, parse_with_options, ParseError};
+use secrets::{FileSecrets, SecretFileError};
+use template::Template;
 use thiserror::Error;
+use vars::Env;
 
+pub mod analysis;
+pub mod dialect;
 pub mod env;
-mod evaluator;
-mod parser;
+pub mod evaluator;
+pub mod export;
+pub mod graph;
+pub mod highlight;
+pub mod import;
+pub mod lint;
+pub mod options;
+pub mod parser;
+pub mod process;
+pub mod secrets;
+pub mod template;
 #[cfg(test)]
 mod test_utils;
 mod tokenizer;
+pub mod vars;
+
+pub use tokenizer::pos::Position;
 
 #[derive(Debug, Error)]
 pub enum PotenvError {
@@ -21,6 +46,8 @@ pub enum PotenvError {
     #[error(transparent)]
     EvaluationError(#[from] EvaluationError),
     #[error(transparent)]
+    SecretFileError(#[from] SecretFileError),
+    #[error(transparent)]
     Io(#[from] std::io::Error),
 }
 
@@ -36,6 +63,28 @@ where
     Potenv::default().load(files)
 }
 
+/// Evaluates a single value, written as the right-hand side of an assignment,
+/// looking up variables in the scope, then in the environment provider,
+/// like [Template::render].
+///
+/// ```
+/// use potenv::evaluator::Scope;
+/// use std::collections::HashMap;
+///
+/// let scope = Scope::from([("DB_HOST".to_owned(), "db".to_owned())]);
+/// let url = potenv::evaluate_value(
+///     "postgres://${DB_HOST:-localhost}:${DB_PORT:-5432}",
+///     &scope,
+///     &HashMap::new(),
+/// );
+/// assert_eq!("postgres://db:5432", url.unwrap());
+/// ```
+pub fn evaluate_value<T: EnvProvider>(value: &str, scope: &Scope, env: &T) -> PotenvResult<String> {
+    let value = parse_value(value, Options::default())?;
+    let mut eval = Evaluator::new(env, true).scope(scope.clone());
+    Ok(eval.evaluate_value(value)?)
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Potenv<T>
 where
@@ -43,6 +92,10 @@ where
 {
     env: T,
     override_env: bool,
+    options: Options,
+    filters: Filters,
+    resolvers: Resolvers,
+    file_secrets: Option<FileSecrets>,
 }
 
 impl Default for Potenv<ProcessEnvProvider> {
@@ -56,7 +109,14 @@ where
     T: EnvProvider,
 {
     pub fn new(env: T, override_env: bool) -> Self {
-        Self { env, override_env }
+        Self {
+            env,
+            override_env,
+            options: Options::default(),
+            filters: Filters::default(),
+            resolvers: Resolvers::default(),
+            file_secrets: None,
+        }
     }
 
     /// Sets whether variables in dotenv files should override those from the environment provider.
@@ -66,6 +126,95 @@ where
         self
     }
 
+    /// Sets whether assignments can be prefixed by the `export` keyword.
+    /// Defaults to false.
+    pub fn export_prefix(mut self, export_prefix: bool) -> Self {
+        self.options.export_prefix = export_prefix;
+        self
+    }
+
+    /// Sets whether `${#NAME}` expansions evaluate to the length of the value of `NAME`.
+    /// Defaults to false.
+    pub fn string_length(mut self, string_length: bool) -> Self {
+        self.options.string_length = string_length;
+        self
+    }
+
+    /// Sets whether `${NAME#pattern}`, `${NAME##pattern}`, `${NAME%pattern}` and `${NAME%%pattern}`
+    /// expansions remove a prefix or suffix matching a pattern from the value of `NAME`.
+    /// Defaults to false.
+    pub fn pattern_removal(mut self, pattern_removal: bool) -> Self {
+        self.options.pattern_removal = pattern_removal;
+        self
+    }
+
+    /// Sets whether the Bash `${NAME:offset:length}` substring and `${NAME/pattern/replacement}`
+    /// replacement expansions are accepted. These are not part of the POSIX shell language.
+    /// Defaults to false.
+    pub fn bash_expansions(mut self, bash_expansions: bool) -> Self {
+        self.options.bash_expansions = bash_expansions;
+        self
+    }
+
+    /// Sets whether an unquoted `~` or `~user` at the start of a value or after a `:`
+    /// expands to the home directory of the current or given user.
+    /// Defaults to false.
+    pub fn tilde_expansion(mut self, tilde_expansion: bool) -> Self {
+        self.options.tilde_expansion = tilde_expansion;
+        self
+    }
+
+    /// Sets whether `${NAME|filter}` expansions transform the value of `NAME` with named filters.
+    /// Defaults to false.
+    pub fn filters(mut self, filters: bool) -> Self {
+        self.options.filters = filters;
+        self
+    }
+
+    /// Registers a filter for `${NAME|filter}` expansions, in addition to the built-in ones
+    /// listed in [Filters].
+    pub fn filter<F>(mut self, name: impl Into<String>, filter: F) -> Self
+    where
+        F: Fn(Value, Option<&str>) -> Result<Value, String> + Send + Sync + 'static,
+    {
+        self.filters.register(name, filter);
+        self
+    }
+
+    /// Sets whether `${namespace:argument}` expansions evaluate to the value
+    /// provided by the resolver registered for `namespace`.
+    /// Defaults to false.
+    pub fn resolvers(mut self, resolvers: bool) -> Self {
+        self.options.resolvers = resolvers;
+        self
+    }
+
+    /// Registers the resolver of a namespace for `${namespace:argument}` expansions.
+    /// Resolvers receive the evaluated argument and return the value of the expansion.
+    pub fn resolver<F>(mut self, namespace: impl Into<String>, resolver: F) -> Self
+    where
+        F: Fn(&str) -> Result<String, String> + Send + Sync + 'static,
+    {
+        self.resolvers.register(namespace, resolver);
+        self
+    }
+
+    /// Sets whether every `X_FILE` variable defines `X` from the content of the file it names,
+    /// after evaluation, as described in [FileSecrets::apply].
+    /// Unless the dotenv files override the environment, `X` must not be set in the environment either.
+    /// Defaults to `None`.
+    pub fn file_secrets(mut self, file_secrets: Option<FileSecrets>) -> Self {
+        self.file_secrets = file_secrets;
+        self
+    }
+
+    /// Sets the rules used to parse dotenv files.
+    /// Defaults to [Dialect::Posix].
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.options.dialect = dialect;
+        self
+    }
+
     /// Loads environment variables from the specified files,
     /// and exports them to the current process's environment.
     pub fn load<I>(&mut self, files: I) -> PotenvResult<impl Iterator<Item = (String, String)>>
@@ -92,18 +241,138 @@ where
         Ok(self.eval(files)?.into_iter())
     }
 
-    fn eval<I>(&self, files: I) -> PotenvResult<Scope>
+    /// Creates a [Command] for the given program, with the variables from the specified files
+    /// added to its environment, without modifying the current process's environment.
+    ///
+    /// If `clear_env` is true, the command will not inherit the current process's environment.
+    pub fn command<I, S>(&self, files: I, program: S, clear_env: bool) -> PotenvResult<Command>
     where
         I: IntoIterator,
         I::Item: AsRef<Path>,
+        S: AsRef<OsStr>,
     {
-        let mut eval = Evaluator::new(&self.env, self.override_env);
+        let scope = self.eval(files)?;
+        let mut cmd = Command::new(program);
+        if clear_env {
+            cmd.env_clear();
+        }
+        cmd.envs(scope);
+        Ok(cmd)
+    }
+
+    /// Evaluates a single value, written as the right-hand side of an assignment,
+    /// looking up variables in the scope, then in the environment provider, like [Potenv::render].
+    /// Values are always parsed following the rules of [Dialect::Posix].
+    pub fn evaluate_value(&self, value: &str, scope: &Scope) -> PotenvResult<String> {
+        let value = parse_value(value, self.posix_options())?;
+        let mut eval = Evaluator::new(&self.env, true)
+            .scope(scope.clone())
+            .filters(self.filters.clone())
+            .resolvers(self.resolvers.clone());
+        Ok(eval.evaluate_value(value)?)
+    }
+
+    /// Parses a template, accepting the expansions enabled on this instance.
+    /// Templates are always parsed following the rules of [Dialect::Posix].
+    pub fn template(&self, source: &str) -> PotenvResult<Template> {
+        Ok(Template::with_options(source, self.posix_options())?)
+    }
+
+    /// Renders a template with the variables from the specified files,
+    /// then those from the environment provider.
+    pub fn render<I>(&self, files: I, template: &Template) -> PotenvResult<String>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+    {
+        let evaluator = Evaluator::new(&self.env, true)
+            .scope(self.eval(files)?)
+            .filters(self.filters.clone())
+            .resolvers(self.resolvers.clone());
+        Ok(template.render_with(evaluator)?)
+    }
+
+    /// Lints the specified files, without evaluating them.
+    pub fn lint<I>(&self, files: I, linter: &Linter) -> PotenvResult<Vec<Diagnostic>>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+    {
+        let mut sources = Vec::new();
+        for file in files {
+            let path = file.as_ref();
+            sources.push((Some(path.to_path_buf()), self.parse(path)?));
+        }
+        Ok(linter.lint(sources))
+    }
+
+    /// Computes the dependency graph of the variables defined in the specified files,
+    /// without evaluating them.
+    pub fn dependencies<I>(&self, files: I) -> PotenvResult<DependencyGraph>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+    {
+        let mut assignments = Vec::new();
+        for file in files {
+            assignments.extend(self.parse(file.as_ref())?);
+        }
+        Ok(DependencyGraph::new(&assignments))
+    }
+
+    /// Loads environment variables from the specified files, along with their provenance,
+    /// without exporting them to the current process's environment.
+    pub fn env<I>(&self, files: I) -> PotenvResult<Env>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+    {
+        let mut eval = Evaluator::new(&self.env, self.override_env)
+            .filters(self.filters.clone())
+            .resolvers(self.resolvers.clone());
         for file in files {
             let path = file.as_ref();
-            let input = std::fs::read_to_string(path)?;
-            let ast = parse(&input, Some(path.to_path_buf()))?;
-            eval.evaluate(ast)?;
+            eval.set_file(Some(path.to_path_buf()));
+            eval.evaluate(self.parse(path)?)?;
+        }
+        let mut env = eval.into_env();
+        if let Some(file_secrets) = &self.file_secrets {
+            if !self.override_env {
+                let conflict = env
+                    .iter()
+                    .filter_map(|(name, _)| name.strip_suffix(secrets::SUFFIX))
+                    .find(|name| !name.is_empty() && self.env.var(name).is_some());
+                if let Some(name) = conflict {
+                    return Err(SecretFileError::Conflict(name.to_owned()).into());
+                }
+            }
+            env.apply_secrets(file_secrets)?;
+        }
+        Ok(env)
+    }
+
+    fn eval<I>(&self, files: I) -> PotenvResult<Scope>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+    {
+        Ok(self.env(files)?.into())
+    }
+
+    fn parse(&self, path: &Path) -> PotenvResult<Vec<Assignment>> {
+        let input = std::fs::read_to_string(path)?;
+        Ok(parse_with_options(
+            &input,
+            Some(path.to_path_buf()),
+            self.options,
+        )?)
+    }
+
+    /// The options for values and templates, which the dialect does not apply to.
+    fn posix_options(&self) -> Options {
+        Options {
+            dialect: Dialect::Posix,
+            ..self.options
         }
-        Ok(eval.into_scope())
     }
 }
@@ -0,0 +1,368 @@
+//! Lints parsed dotenv files for likely mistakes.
+//!
+//! ```
+//! use potenv::{lint::{Level, Linter, Rule}, parser::parse};
+//!
+//! let ast = parse("url=http://${HOST}/\nDEBUG=", None).unwrap();
+//! let linter = Linter::new().rule(Rule::EmptyValue, Level::Allow);
+//! let diagnostics = linter.lint([(None, ast)]);
+//! let rules: Vec<_> = diagnostics.iter().map(|d| d.rule).collect();
+//! assert_eq!(vec![Rule::UndefinedReference, Rule::LowercaseName], rules);
+//! ```
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use indexmap::IndexMap;
+use thiserror::Error;
+
+use crate::{
+    parser::ast::{Assignment, Expression, Operator},
+    tokenizer::pos::Position,
+};
+
+#[cfg(feature = "json")]
+pub mod report;
+#[cfg(test)]
+mod tests;
+
+/// A check performed by the [Linter].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+    /// A variable is assigned more than once, possibly in different files.
+    DuplicateKey,
+    /// A variable name contains lowercase letters.
+    LowercaseName,
+    /// A variable is referenced without a default value, but no assignment defines it beforehand.
+    ///
+    /// `${NAME?}` and `${NAME:?}` are reported too, since they fail instead of providing a default.
+    UndefinedReference,
+    /// A variable is assigned by a `${NAME:=value}` expansion, but never referenced afterwards.
+    UnusedAssignment,
+    /// A variable is assigned an empty value.
+    EmptyValue,
+    /// A variable value ends with whitespace.
+    TrailingWhitespace,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 6] = [
+        Rule::DuplicateKey,
+        Rule::LowercaseName,
+        Rule::UndefinedReference,
+        Rule::UnusedAssignment,
+        Rule::EmptyValue,
+        Rule::TrailingWhitespace,
+    ];
+
+    /// Returns the identifier of the rule, as used in configuration and reports.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::DuplicateKey => "duplicate-key",
+            Self::LowercaseName => "lowercase-name",
+            Self::UndefinedReference => "undefined-reference",
+            Self::UnusedAssignment => "unused-assignment",
+            Self::EmptyValue => "empty-value",
+            Self::TrailingWhitespace => "trailing-whitespace",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::DuplicateKey => "Variables should be assigned only once",
+            Self::LowercaseName => "Variable names should be uppercase",
+            Self::UndefinedReference => "Referenced variables should be defined beforehand",
+            Self::UnusedAssignment => "Variables assigned by expansions should be referenced",
+            Self::EmptyValue => "Variables should not be empty",
+            Self::TrailingWhitespace => "Values should not end with whitespace",
+        }
+    }
+}
+
+impl std::fmt::Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("Unknown lint rule '{0}'")]
+pub struct UnknownRule(pub String);
+
+impl FromStr for Rule {
+    type Err = UnknownRule;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|rule| rule.code() == s)
+            .ok_or_else(|| UnknownRule(s.to_owned()))
+    }
+}
+
+/// The level at which a rule is reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    /// The rule is disabled.
+    Allow,
+    Warning,
+    Error,
+}
+
+impl std::fmt::Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Allow => "allow",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        })
+    }
+}
+
+/// An issue reported by the [Linter].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub rule: Rule,
+    pub level: Level,
+    pub message: String,
+    /// The file containing the offending assignment, if known.
+    pub file: Option<PathBuf>,
+    /// The position of the offending assignment.
+    pub position: Position,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(file) = self.file.as_ref() {
+            f.write_fmt(format_args!("{}:", file.display()))?;
+        }
+        f.write_fmt(format_args!(
+            "{}:{}: {}: {} [{}]",
+            self.position.line, self.position.column, self.level, self.message, self.rule
+        ))
+    }
+}
+
+/// Checks parsed dotenv files against a set of rules.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Linter {
+    levels: HashMap<Rule, Level>,
+}
+
+impl Linter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the level at which a rule is reported. All rules default to [Level::Warning].
+    pub fn rule(mut self, rule: Rule, level: Level) -> Self {
+        self.levels.insert(rule, level);
+        self
+    }
+
+    /// Returns the level at which a rule is reported.
+    pub fn level(&self, rule: Rule) -> Level {
+        self.levels.get(&rule).copied().unwrap_or(Level::Warning)
+    }
+
+    /// Lints the assignments of the given files, in evaluation order.
+    /// Diagnostics are returned in the order of the assignments they refer to.
+    pub fn lint<I>(&self, files: I) -> Vec<Diagnostic>
+    where
+        I: IntoIterator<Item = (Option<PathBuf>, Vec<Assignment>)>,
+    {
+        let files: Vec<_> = files.into_iter().collect();
+        let mut pass = Pass {
+            linter: self,
+            diagnostics: Vec::new(),
+            assigned: HashMap::new(),
+            defined: HashMap::new(),
+            pending: IndexMap::new(),
+        };
+        for (index, (file, assignments)) in files.iter().enumerate() {
+            for assignment in assignments {
+                let location = Location {
+                    index,
+                    file: file.as_deref(),
+                    position: assignment.position,
+                };
+                pass.visit_assignment(assignment, location);
+            }
+        }
+        let pending = std::mem::take(&mut pass.pending);
+        for (name, location) in pending {
+            pass.report_unused(name, location);
+        }
+        pass.diagnostics
+            .sort_by_key(|(location, _)| (location.index, location.position));
+        pass.diagnostics.into_iter().map(|(_, d)| d).collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Location<'a> {
+    /// The index of the file, to sort diagnostics.
+    index: usize,
+    file: Option<&'a Path>,
+    position: Position,
+}
+
+impl std::fmt::Display for Location<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(file) = self.file {
+            f.write_fmt(format_args!("{} ", file.display()))?;
+        }
+        f.write_fmt(format_args!("line {}", self.position.line))
+    }
+}
+
+struct Pass<'a> {
+    linter: &'a Linter,
+    diagnostics: Vec<(Location<'a>, Diagnostic)>,
+    /// Variables defined by assignments.
+    assigned: HashMap<&'a str, Location<'a>>,
+    /// Variables defined by assignments or assigning expansions.
+    defined: HashMap<&'a str, Location<'a>>,
+    /// Variables defined by assigning expansions, and not referenced since.
+    pending: IndexMap<&'a str, Location<'a>>,
+}
+
+impl<'a> Pass<'a> {
+    fn visit_assignment(&mut self, node: &'a Assignment, location: Location<'a>) {
+        self.visit_value(&node.value, location);
+        let name = node.name.as_str();
+        if let Some(previous) = self.assigned.get(name) {
+            let message = format!("'{name}' is already defined at {previous}");
+            self.report(Rule::DuplicateKey, message, location);
+        }
+        if let Some(expansion) = self.pending.shift_remove(name) {
+            self.report_unused(name, expansion);
+        }
+        if name.chars().any(char::is_lowercase) {
+            let message = format!("'{name}' should be uppercase");
+            self.report(Rule::LowercaseName, message, location);
+        }
+        let value = node
+            .value
+            .iter()
+            .try_fold(String::new(), |mut value, expr| {
+                match expr {
+                    Expression::Characters(chars) => value.push_str(chars),
+                    Expression::Expansion(_)
+                    | Expression::Length(_)
+                    | Expression::Substring(_)
+                    | Expression::Replacement(_)
+                    | Expression::Filtered(_)
+                    | Expression::Resolved(_)
+                    | Expression::Tilde(_)
+                    | Expression::Quoted(_) => return None,
+                }
+                Some(value)
+            });
+        if value.as_deref() == Some("") {
+            let message = format!("'{name}' has an empty value");
+            self.report(Rule::EmptyValue, message, location);
+        }
+        if let Some(Expression::Characters(chars)) = node.value.last() {
+            if chars.ends_with(char::is_whitespace) {
+                let message = format!("The value of '{name}' ends with whitespace");
+                self.report(Rule::TrailingWhitespace, message, location);
+            }
+        }
+        self.assigned.insert(name, location);
+        self.defined.insert(name, location);
+    }
+
+    fn visit_value(&mut self, value: &'a [Expression], location: Location<'a>) {
+        for expr in value {
+            let expansion = match expr {
+                Expression::Characters(_) | Expression::Tilde(_) => continue,
+                Expression::Length(node) => {
+                    self.visit_reference(&node.name, location);
+                    continue;
+                }
+                Expression::Substring(node) => {
+                    self.visit_reference(&node.name, location);
+                    continue;
+                }
+                Expression::Filtered(node) => {
+                    self.visit_reference(&node.name, location);
+                    continue;
+                }
+                Expression::Resolved(node) => {
+                    self.visit_value(&node.argument, location);
+                    continue;
+                }
+                Expression::Replacement(node) => {
+                    self.visit_reference(&node.name, location);
+                    self.visit_value(&node.pattern, location);
+                    self.visit_value(&node.replacement, location);
+                    continue;
+                }
+                Expression::Quoted(node) => {
+                    self.visit_value(std::slice::from_ref(node), location);
+                    continue;
+                }
+                Expression::Expansion(expansion) => expansion,
+            };
+            let name = expansion.name.as_str();
+            let has_default = !expansion.plain
+                && !matches!(
+                    expansion.operator,
+                    Operator::ErrorIfUnset
+                        | Operator::ErrorIfUnsetOrNull
+                        | Operator::RemoveSmallestPrefix
+                        | Operator::RemoveLargestPrefix
+                        | Operator::RemoveSmallestSuffix
+                        | Operator::RemoveLargestSuffix
+                );
+            if !has_default {
+                self.visit_reference(name, location);
+            } else {
+                self.pending.shift_remove(name);
+            }
+            self.visit_value(&expansion.rhs, location);
+            if matches!(
+                expansion.operator,
+                Operator::AssignIfUnset | Operator::AssignIfUnsetOrNull
+            ) {
+                self.defined.entry(name).or_insert(location);
+                if let Some(previous) = self.pending.insert(name, location) {
+                    self.report_unused(name, previous);
+                }
+            }
+        }
+    }
+
+    /// Visits a reference to a variable without a default value.
+    fn visit_reference(&mut self, name: &'a str, location: Location<'a>) {
+        self.pending.shift_remove(name);
+        if !self.defined.contains_key(name) {
+            let message = format!("'{name}' is referenced but not defined beforehand");
+            self.report(Rule::UndefinedReference, message, location);
+        }
+    }
+
+    fn report_unused(&mut self, name: &str, location: Location<'a>) {
+        let message = format!("'{name}' is assigned by an expansion but never referenced");
+        self.report(Rule::UnusedAssignment, message, location);
+    }
+
+    fn report(&mut self, rule: Rule, message: String, location: Location<'a>) {
+        let level = self.linter.level(rule);
+        if level == Level::Allow {
+            return;
+        }
+        let diagnostic = Diagnostic {
+            rule,
+            level,
+            message,
+            file: location.file.map(Path::to_path_buf),
+            position: location.position,
+        };
+        self.diagnostics.push((location, diagnostic));
+    }
+}
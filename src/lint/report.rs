@@ -0,0 +1,77 @@
+//! Machine-readable reports of lint diagnostics.
+
+use serde_json::{json, Value};
+
+use super::{Diagnostic, Level, Rule};
+
+/// Renders diagnostics as a JSON array.
+pub fn json(diagnostics: &[Diagnostic]) -> serde_json::Result<String> {
+    let diagnostics: Vec<_> = diagnostics
+        .iter()
+        .map(|d| {
+            json!({
+                "rule": d.rule.code(),
+                "level": d.level.to_string(),
+                "message": d.message,
+                "file": d.file,
+                "line": d.position.line,
+                "column": d.position.column,
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&diagnostics)
+}
+
+/// Renders diagnostics as a [SARIF](https://sarifweb.azurewebsites.net/) 2.1.0 log.
+pub fn sarif(diagnostics: &[Diagnostic]) -> serde_json::Result<String> {
+    let rules: Vec<_> = Rule::ALL
+        .iter()
+        .map(|rule| {
+            json!({
+                "id": rule.code(),
+                "shortDescription": {"text": rule.description()},
+            })
+        })
+        .collect();
+    let results: Vec<_> = diagnostics.iter().map(sarif_result).collect();
+    let log = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "potenv",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    });
+    serde_json::to_string_pretty(&log)
+}
+
+fn sarif_result(diagnostic: &Diagnostic) -> Value {
+    let mut result = json!({
+        "ruleId": diagnostic.rule.code(),
+        "ruleIndex": Rule::ALL.iter().position(|r| *r == diagnostic.rule),
+        "level": match diagnostic.level {
+            Level::Error => "error",
+            _ => "warning",
+        },
+        "message": {"text": diagnostic.message},
+    });
+    if let Some(file) = diagnostic.file.as_ref() {
+        result["locations"] = json!([{
+            "physicalLocation": {
+                "artifactLocation": {"uri": file.to_string_lossy().replace('\\', "/")},
+                "region": {
+                    "startLine": diagnostic.position.line,
+                    "startColumn": diagnostic.position.column,
+                },
+            },
+        }]);
+    }
+    result
+}
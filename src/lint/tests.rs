@@ -0,0 +1,131 @@
+use std::path::PathBuf;
+
+use rstest::rstest;
+
+use super::{Level, Linter, Rule, UnknownRule};
+use crate::{
+    options::Options,
+    parser::{parse, parse_with_options},
+    tokenizer::pos::Position,
+};
+
+fn lint(linter: &Linter, files: &[&str]) -> Vec<(Rule, Option<PathBuf>, usize)> {
+    let options = Options {
+        string_length: true,
+        pattern_removal: true,
+        bash_expansions: true,
+        filters: true,
+        resolvers: true,
+        tilde_expansion: true,
+        ..Default::default()
+    };
+    let files = files.iter().enumerate().map(|(i, input)| {
+        let path = PathBuf::from(format!("{i}.env"));
+        (
+            Some(path),
+            parse_with_options(input, None, options).unwrap(),
+        )
+    });
+    linter
+        .lint(files)
+        .into_iter()
+        .map(|d| (d.rule, d.file, d.position.line))
+        .collect()
+}
+
+#[rstest]
+#[case::duplicate_key("A=1\nA=2", vec![(Rule::DuplicateKey, 2)])]
+#[case::lowercase_name("a=1\nA_b=2\nA_1=3", vec![(Rule::LowercaseName, 1), (Rule::LowercaseName, 2)])]
+#[case::undefined_reference("A=$B\nB=1\nC=$B", vec![(Rule::UndefinedReference, 1)])]
+#[case::string_length("A=${#B}\nC=${#A}", vec![(Rule::UndefinedReference, 1)])]
+#[case::pattern_removal("A=${B%/}", vec![(Rule::UndefinedReference, 1)])]
+#[case::substring("A=${B:1}", vec![(Rule::UndefinedReference, 1)])]
+#[case::replacement("A=1\nB=${A/x/$C}", vec![(Rule::UndefinedReference, 2)])]
+#[case::filtered("A=${B|upper}", vec![(Rule::UndefinedReference, 1)])]
+#[case::resolved("A=${file:$B}\nC=${file:/a}", vec![(Rule::UndefinedReference, 1)])]
+#[case::tilde("A=~", vec![])]
+#[case::reference_with_default("A=${B:-1}${C-}${D:+1}", vec![])]
+#[case::if_unset_without_word("A=${B-}", vec![])]
+#[case::error_if_unset("A=${B?}\nC=${D:?unset}", vec![(Rule::UndefinedReference, 1), (Rule::UndefinedReference, 2)])]
+#[case::error_if_unset_or_null("A=${B:?}", vec![(Rule::UndefinedReference, 1)])]
+#[case::plain_braced_reference("A=${B}", vec![(Rule::UndefinedReference, 1)])]
+#[case::reference_to_assigned_expansion("A=${B:=1}\nC=${B}", vec![])]
+#[case::unused_assignment("A=${B:=1}", vec![(Rule::UnusedAssignment, 1)])]
+#[case::overridden_assignment("A=${B:=1}\nB=2\nC=$B", vec![(Rule::UnusedAssignment, 1)])]
+#[case::used_assignment("A=${B:=1}\nC=${B}", vec![])]
+#[case::empty_value("A=\nB=''\nC=$A", vec![(Rule::EmptyValue, 1), (Rule::EmptyValue, 2)])]
+#[case::trailing_whitespace("A='a '\nB=\"b\n\"\nC=' c'", vec![(Rule::TrailingWhitespace, 1), (Rule::TrailingWhitespace, 2)])]
+fn test_rules(#[case] input: &str, #[case] expected: Vec<(Rule, usize)>) {
+    let diagnostics: Vec<_> = lint(&Linter::new(), &[input])
+        .into_iter()
+        .map(|(rule, _, line)| (rule, line))
+        .collect();
+    assert_eq!(expected, diagnostics);
+}
+
+#[test]
+fn test_export_prefix_position() {
+    let options = Options {
+        export_prefix: true,
+        ..Default::default()
+    };
+    let ast = parse_with_options("export a=1", None, options).unwrap();
+    let diagnostics = Linter::new().lint([(None, ast)]);
+    assert_eq!(Rule::LowercaseName, diagnostics[0].rule);
+    assert_eq!(Position::new(1, 8), diagnostics[0].position);
+}
+
+#[test]
+fn test_across_files() {
+    let diagnostics = lint(&Linter::new(), &["A=${B:=1}\nC=1", "C=$B", "C=2"]);
+    assert_eq!(
+        vec![
+            (Rule::DuplicateKey, Some(PathBuf::from("1.env")), 1),
+            (Rule::DuplicateKey, Some(PathBuf::from("2.env")), 1),
+        ],
+        diagnostics
+    );
+}
+
+#[test]
+fn test_levels() {
+    let linter = Linter::new()
+        .rule(Rule::LowercaseName, Level::Allow)
+        .rule(Rule::EmptyValue, Level::Error);
+    assert_eq!(Level::Warning, linter.level(Rule::DuplicateKey));
+    let diagnostics = linter.lint([(None, parse("a=\nA=", None).unwrap())]);
+    let levels: Vec<_> = diagnostics.iter().map(|d| (d.rule, d.level)).collect();
+    assert_eq!(
+        vec![
+            (Rule::EmptyValue, Level::Error),
+            (Rule::EmptyValue, Level::Error)
+        ],
+        levels
+    );
+}
+
+#[test]
+fn test_rule_codes() {
+    for rule in Rule::ALL {
+        assert_eq!(Ok(rule), rule.code().parse());
+    }
+    assert_eq!(Err(UnknownRule("nope".into())), "nope".parse::<Rule>());
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_sarif_report() {
+    let diagnostics = Linter::new().lint([(
+        Some(PathBuf::from("dir/.env")),
+        parse("A=1\n  A=2", None).unwrap(),
+    )]);
+    let log: serde_json::Value =
+        serde_json::from_str(&super::report::sarif(&diagnostics).unwrap()).unwrap();
+    let result = &log["runs"][0]["results"][0];
+    assert_eq!("duplicate-key", result["ruleId"]);
+    assert_eq!("warning", result["level"]);
+    let location = &result["locations"][0]["physicalLocation"];
+    assert_eq!("dir/.env", location["artifactLocation"]["uri"]);
+    assert_eq!(2, location["region"]["startLine"]);
+    assert_eq!(3, location["region"]["startColumn"]);
+}
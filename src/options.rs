@@ -0,0 +1,36 @@
+//! Options enabling syntax that is not part of the specification.
+
+use crate::dialect::Dialect;
+
+/// Parsing options. All of them are disabled by default, for strict spec compliance.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// Accept a leading `export` keyword before assignments,
+    /// as found in dotenv files that are also sourced by shell scripts.
+    pub export_prefix: bool,
+    /// Accept `${#NAME}` expansions, which evaluate to the number of characters
+    /// in the value of `NAME`.
+    pub string_length: bool,
+    /// Accept the `${NAME#pattern}`, `${NAME##pattern}`, `${NAME%pattern}` and `${NAME%%pattern}`
+    /// expansions, which remove the smallest or largest prefix or suffix matching a pattern.
+    pub pattern_removal: bool,
+    /// Accept the Bash `${NAME:offset:length}` substring and `${NAME/pattern/replacement}`
+    /// replacement expansions. These are not part of the POSIX shell language.
+    pub bash_expansions: bool,
+    /// Accept `${NAME|filter}` expansions, which transform the value of `NAME`
+    /// with a chain of named filters, e.g. `${HOSTS|split:,|first}`.
+    pub filters: bool,
+    /// Accept `${namespace:argument}` expansions, which evaluate to the value
+    /// provided by the resolver registered for `namespace`, e.g. `${file:/run/secrets/db}`.
+    /// The argument cannot start with an operator character or, with
+    /// [Options::bash_expansions], a digit or a blank.
+    pub resolvers: bool,
+    /// Expand an unquoted `~` or `~user` at the start of a value or after a `:`
+    /// to the home directory of the current or given user, as a POSIX shell does in assignments.
+    /// `~` expands to `$HOME`, and `~user` is kept literally if the user is unknown,
+    /// or on platforms other than Unix.
+    pub tilde_expansion: bool,
+    /// Parse files following the rules of another dotenv implementation.
+    /// Other options do not apply to dialects other than [Dialect::Posix].
+    pub dialect: Dialect,
+}
@@ -1,7 +1,17 @@
+use crate::tokenizer::pos::Position;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expression {
     Characters(String),
     Expansion(Expansion),
+    Length(Length),
+    Substring(Substring),
+    Replacement(Replacement),
+    Filtered(Filtered),
+    Resolved(Resolved),
+    Tilde(Tilde),
+    /// An expansion double-quoted inside a pattern, whose value matches literally.
+    Quoted(Box<Expression>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -14,17 +24,39 @@ pub enum Operator {
     AssignIfUnsetOrNull,
     ErrorIfUnset,
     ErrorIfUnsetOrNull,
+    /// `${NAME#pattern}`
+    RemoveSmallestPrefix,
+    /// `${NAME##pattern}`
+    RemoveLargestPrefix,
+    /// `${NAME%pattern}`
+    RemoveSmallestSuffix,
+    /// `${NAME%%pattern}`
+    RemoveLargestSuffix,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Assignment {
     pub name: String,
     pub value: Vec<Expression>,
+    /// Whether the assignment is prefixed by the `export` keyword.
+    pub export: bool,
+    /// The position of the variable name in its dotenv file.
+    pub position: Position,
 }
 
 impl Assignment {
     pub fn new(name: String, value: Vec<Expression>) -> Self {
-        Self { name, value }
+        Self {
+            name,
+            value,
+            export: false,
+            position: Position::default(),
+        }
+    }
+
+    pub(crate) fn at(mut self, position: Position) -> Self {
+        self.position = position;
+        self
     }
 }
 
@@ -33,6 +65,10 @@ pub struct Expansion {
     pub name: String,
     pub operator: Operator,
     pub rhs: Vec<Expression>,
+    /// The position of the variable name in its dotenv file.
+    pub position: Position,
+    /// Whether the expansion is written without an operator, i.e. `$NAME` or `${NAME}`.
+    pub plain: bool,
 }
 
 impl Expansion {
@@ -41,6 +77,199 @@ impl Expansion {
             name,
             operator,
             rhs,
+            position: Position::default(),
+            plain: false,
+        }
+    }
+
+    /// Creates an expansion without an operator, i.e. `$NAME` or `${NAME}`.
+    pub fn plain(name: String) -> Self {
+        Self {
+            plain: true,
+            ..Self::new(name, Operator::IfUnset, vec![])
+        }
+    }
+
+    pub(crate) fn at(mut self, position: Position) -> Self {
+        self.position = position;
+        self
+    }
+}
+
+/// The number of characters in the value of a variable, i.e. `${#NAME}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Length {
+    pub name: String,
+    /// The position of the variable name in its dotenv file.
+    pub position: Position,
+}
+
+impl Length {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            position: Position::default(),
         }
     }
+
+    pub(crate) fn at(mut self, position: Position) -> Self {
+        self.position = position;
+        self
+    }
+}
+
+/// A substring of the value of a variable, i.e. `${NAME:offset}` or `${NAME:offset:length}`.
+/// This is a Bash extension, not part of the POSIX shell language.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Substring {
+    pub name: String,
+    /// The offset of the first character, counted from the end of the value when negative.
+    pub offset: i64,
+    /// The number of characters, or the offset of the end counted from the end of the value
+    /// when negative. The substring extends to the end of the value when missing.
+    pub length: Option<i64>,
+    /// The position of the variable name in its dotenv file.
+    pub position: Position,
+}
+
+impl Substring {
+    pub fn new(name: String, offset: i64, length: Option<i64>) -> Self {
+        Self {
+            name,
+            offset,
+            length,
+            position: Position::default(),
+        }
+    }
+
+    pub(crate) fn at(mut self, position: Position) -> Self {
+        self.position = position;
+        self
+    }
+}
+
+/// Which matches of the pattern a [Replacement] replaces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplacementMode {
+    /// `${NAME/pattern/replacement}`
+    First,
+    /// `${NAME//pattern/replacement}`
+    All,
+    /// `${NAME/#pattern/replacement}`
+    Prefix,
+    /// `${NAME/%pattern/replacement}`
+    Suffix,
+}
+
+/// The value of a variable with the longest matches of a pattern replaced,
+/// i.e. `${NAME/pattern/replacement}`.
+/// This is a Bash extension, not part of the POSIX shell language.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replacement {
+    pub name: String,
+    pub mode: ReplacementMode,
+    pub pattern: Vec<Expression>,
+    pub replacement: Vec<Expression>,
+    /// The position of the variable name in its dotenv file.
+    pub position: Position,
+}
+
+impl Replacement {
+    pub fn new(
+        name: String,
+        mode: ReplacementMode,
+        pattern: Vec<Expression>,
+        replacement: Vec<Expression>,
+    ) -> Self {
+        Self {
+            name,
+            mode,
+            pattern,
+            replacement,
+            position: Position::default(),
+        }
+    }
+
+    pub(crate) fn at(mut self, position: Position) -> Self {
+        self.position = position;
+        self
+    }
+}
+
+/// The value of a variable transformed by a chain of filters, i.e. `${NAME|filter:argument}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filtered {
+    pub name: String,
+    pub filters: Vec<Filter>,
+    /// The position of the variable name in its dotenv file.
+    pub position: Position,
+}
+
+impl Filtered {
+    pub fn new(name: String, filters: Vec<Filter>) -> Self {
+        Self {
+            name,
+            filters,
+            position: Position::default(),
+        }
+    }
+
+    pub(crate) fn at(mut self, position: Position) -> Self {
+        self.position = position;
+        self
+    }
+}
+
+/// A filter applied by a [Filtered] expansion, with the text following its name and a `:`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    pub name: String,
+    pub argument: Option<String>,
+}
+
+/// A value provided by the resolver of a namespace, i.e. `${namespace:argument}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolved {
+    pub namespace: String,
+    pub argument: Vec<Expression>,
+    /// The position of the namespace in its dotenv file.
+    pub position: Position,
+}
+
+impl Resolved {
+    pub fn new(namespace: String, argument: Vec<Expression>) -> Self {
+        Self {
+            namespace,
+            argument,
+            position: Position::default(),
+        }
+    }
+
+    pub(crate) fn at(mut self, position: Position) -> Self {
+        self.position = position;
+        self
+    }
+}
+
+/// The home directory of a user, i.e. `~` or `~user`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tilde {
+    /// The login name of the user, or an empty string for the current user.
+    pub user: String,
+    /// The position of the `~` in its dotenv file.
+    pub position: Position,
+}
+
+impl Tilde {
+    pub fn new(user: String) -> Self {
+        Self {
+            user,
+            position: Position::default(),
+        }
+    }
+
+    pub(crate) fn at(mut self, position: Position) -> Self {
+        self.position = position;
+        self
+    }
 }
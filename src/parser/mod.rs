@@ -7,10 +7,14 @@ use std::{iter::Peekable, path::PathBuf};
 use thiserror::Error;
 
 use self::ast::*;
-use crate::tokenizer::{
-    err::SyntaxError,
-    token::{Token, TokenKind},
-    Tokenizer, TokenizerResult,
+use crate::{
+    dialect::{self, Dialect},
+    options::Options,
+    tokenizer::{
+        err::SyntaxError,
+        token::{Token, TokenKind},
+        Tokenizer, TokenizerResult,
+    },
 };
 
 #[derive(Debug, Error, PartialEq, Eq)]
@@ -23,6 +27,8 @@ pub enum ParseError {
     UnknownOperator(String),
     #[error(transparent)]
     Syntax(#[from] SyntaxError),
+    #[error("The {0:?} dialect only applies to dotenv files")]
+    UnsupportedDialect(Dialect),
 }
 
 pub type ParseResult<T> = Result<T, ParseError>;
@@ -32,6 +38,49 @@ pub fn parse(input: &str, filename: Option<PathBuf>) -> ParseResult<Vec<Assignme
     Parser::new(tokenizer).parse()
 }
 
+pub fn parse_with_options(
+    input: &str,
+    filename: Option<PathBuf>,
+    options: Options,
+) -> ParseResult<Vec<Assignment>> {
+    match options.dialect {
+        Dialect::Posix => {
+            let tokenizer = Tokenizer::with_options(input.chars(), filename, options);
+            Parser::new(tokenizer).parse()
+        }
+        Dialect::PythonDotenv => Ok(dialect::python::parse(input)),
+        Dialect::Node => Ok(dialect::node::parse(input)),
+        Dialect::Ruby => dialect::ruby::parse(input, filename),
+        Dialect::DockerCompose => dialect::compose::parse(input, filename),
+    }
+}
+
+/// Parses a template, i.e. text where only expansions are recognized, as in `envsubst`.
+/// Quotes and backslashes are kept literally, except inside the value of an expansion.
+/// Fails for dialects other than [Dialect::Posix].
+pub fn parse_template(input: &str, options: Options) -> ParseResult<Vec<Expression>> {
+    require_posix(options)?;
+    let tokenizer = Tokenizer::with_options(input.chars(), None, options).template();
+    Parser::new(tokenizer).parse_value()
+}
+
+/// Parses a single value, as the right-hand side of an assignment.
+/// The value ends at the first unquoted blank, which can only be followed by a comment.
+/// Fails for dialects other than [Dialect::Posix].
+pub fn parse_value(input: &str, options: Options) -> ParseResult<Vec<Expression>> {
+    require_posix(options)?;
+    let tokenizer = Tokenizer::with_options(input.chars(), None, options).value();
+    Parser::new(tokenizer).parse_value()
+}
+
+/// Fails unless the options use the dialect the tokenizer implements.
+pub(crate) fn require_posix(options: Options) -> ParseResult<()> {
+    match options.dialect {
+        Dialect::Posix => Ok(()),
+        dialect => Err(ParseError::UnsupportedDialect(dialect)),
+    }
+}
+
 macro_rules! match_kind {
     ($($kind:ident)|+) => {
         $( Some(Ok(Token {kind: TokenKind::$kind, ..})) )|+
@@ -62,7 +111,7 @@ where
                 None => return Err(ParseError::Eof),
                 Some(Err(_)) => return self.take_err(),
                 match_kind!(Eof) => return Ok(nodes),
-                match_kind!(Assign) => nodes.push(self.parse_assignment()?),
+                match_kind!(Export | Assign) => nodes.push(self.parse_assignment()?),
                 Some(Ok(_)) => {
                     return Err(ParseError::Unexpected(self.take_cur()?));
                 }
@@ -70,10 +119,23 @@ where
         }
     }
 
+    /// Parses a value or a template, up to the end of input.
+    pub fn parse_value(&mut self) -> ParseResult<Vec<Expression>> {
+        let nodes = self.parse_assignment_value()?;
+        self.expect(TokenKind::Eof)?;
+        Ok(nodes)
+    }
+
     fn parse_assignment(&mut self) -> ParseResult<Assignment> {
-        let name = self.take_cur()?.value;
+        let mut token = self.take_cur()?;
+        let export = token.kind == TokenKind::Export;
+        if export {
+            token = self.expect(TokenKind::Assign)?;
+        }
         let value = self.parse_assignment_value()?;
-        Ok(Assignment::new(name, value))
+        let mut node = Assignment::new(token.value, value).at(token.position);
+        node.export = export;
+        Ok(node)
     }
 
     fn parse_assignment_value(&mut self) -> ParseResult<Vec<Expression>> {
@@ -82,22 +144,17 @@ where
             match self.tokens.peek() {
                 None => return Err(ParseError::Eof),
                 Some(Err(_)) => return self.take_err(),
-                match_kind!(Eof | Assign) => return Ok(nodes),
+                match_kind!(Eof | Export | Assign) => return Ok(nodes),
                 match_kind!(Characters) => {
                     nodes.push(Expression::Characters(self.take_cur()?.value));
                 }
-                match_kind!(SimpleExpansion) => {
-                    nodes.push(Expression::Expansion(Expansion::new(
-                        self.take_cur()?.value,
-                        Operator::IfUnset,
-                        vec![],
-                    )));
+                match_kind!(Tilde) => {
+                    let token = self.take_cur()?;
+                    let user = token.value.trim_start_matches('~').to_owned();
+                    nodes.push(Expression::Tilde(Tilde::new(user).at(token.position)));
                 }
-                match_kind!(StartExpansion) => {
-                    let name = self.take_cur()?.value;
-                    let operator = self.parse_operator()?;
-                    let rhs = self.parse_expansion_value()?;
-                    nodes.push(Expression::Expansion(Expansion::new(name, operator, rhs)));
+                match_kind!(SimpleExpansion | StartExpansion | StringLength) => {
+                    nodes.push(self.parse_expansion()?);
                 }
                 Some(Ok(_)) => {
                     return Err(ParseError::Unexpected(self.take_cur()?));
@@ -107,30 +164,24 @@ where
     }
 
     fn parse_expansion_value(&mut self) -> ParseResult<Vec<Expression>> {
+        let nodes = self.parse_words()?;
+        self.expect(TokenKind::EndExpansion)?;
+        Ok(nodes)
+    }
+
+    /// Parses the words of an expansion value, up to the next operator or the end of the expansion.
+    fn parse_words(&mut self) -> ParseResult<Vec<Expression>> {
         let mut nodes = Vec::new();
         loop {
             match self.tokens.peek() {
                 None => return Err(ParseError::Eof),
                 Some(Err(_)) => return self.take_err(),
-                match_kind!(EndExpansion) => {
-                    self.tokens.next();
-                    return Ok(nodes);
-                }
+                match_kind!(EndExpansion | ExpansionOperator) => return Ok(nodes),
                 match_kind!(Characters) => {
                     nodes.push(Expression::Characters(self.take_cur()?.value));
                 }
-                match_kind!(SimpleExpansion) => {
-                    nodes.push(Expression::Expansion(Expansion::new(
-                        self.take_cur()?.value,
-                        Operator::IfUnset,
-                        vec![],
-                    )));
-                }
-                match_kind!(StartExpansion) => {
-                    let name = self.take_cur()?.value;
-                    let operator = self.parse_operator()?;
-                    let rhs = self.parse_expansion_value()?;
-                    nodes.push(Expression::Expansion(Expansion::new(name, operator, rhs)));
+                match_kind!(SimpleExpansion | StartExpansion | StringLength) => {
+                    nodes.push(self.parse_expansion()?);
                 }
                 Some(Ok(_)) => {
                     return Err(ParseError::Unexpected(self.take_cur()?));
@@ -139,21 +190,118 @@ where
         }
     }
 
-    fn parse_operator(&mut self) -> ParseResult<Operator> {
-        let token = self.expect(TokenKind::ExpansionOperator)?;
-        match token.value.as_str() {
-            "-" => Ok(Operator::IfUnset),
-            ":-" => Ok(Operator::IfUnsetOrNull),
-            "=" => Ok(Operator::AssignIfUnset),
-            ":=" => Ok(Operator::AssignIfUnsetOrNull),
-            "+" => Ok(Operator::IfSet),
-            ":+" => Ok(Operator::IfSetAndNotNull),
-            "?" => Ok(Operator::ErrorIfUnset),
-            ":?" => Ok(Operator::ErrorIfUnsetOrNull),
-            op => Err(ParseError::UnknownOperator(op.to_owned())),
+    fn parse_expansion(&mut self) -> ParseResult<Expression> {
+        let token = self.take_cur()?;
+        let node = match token.kind {
+            TokenKind::StringLength => {
+                Expression::Length(Length::new(token.value).at(token.position))
+            }
+            TokenKind::StartExpansion if matches!(self.tokens.peek(), match_kind!(Filter)) => {
+                let node = self.parse_filters(token.value)?;
+                Expression::Filtered(node.at(token.position))
+            }
+            TokenKind::StartExpansion if matches!(self.tokens.peek(), match_kind!(Resolve)) => {
+                self.tokens.next();
+                let argument = self.parse_expansion_value()?;
+                Expression::Resolved(Resolved::new(token.value, argument).at(token.position))
+            }
+            TokenKind::StartExpansion => {
+                let operator = self.expect(TokenKind::ExpansionOperator)?;
+                match operator.value.as_str() {
+                    ":" => {
+                        let node = self.parse_substring(token.value)?;
+                        Expression::Substring(node.at(token.position))
+                    }
+                    "/" | "//" | "/#" | "/%" => {
+                        let node = self.parse_replacement(token.value, &operator.value)?;
+                        Expression::Replacement(node.at(token.position))
+                    }
+                    op => {
+                        let operator = parse_operator(op)?;
+                        let rhs = self.parse_expansion_value()?;
+                        let expansion = Expansion::new(token.value, operator, rhs);
+                        Expression::Expansion(expansion.at(token.position))
+                    }
+                }
+            }
+            _ => {
+                let expansion = Expansion::plain(token.value);
+                Expression::Expansion(expansion.at(token.position))
+            }
+        };
+        match token.quoted {
+            true => Ok(Expression::Quoted(Box::new(node))),
+            false => Ok(node),
         }
     }
 
+    fn parse_substring(&mut self, name: String) -> ParseResult<Substring> {
+        let offset = self.parse_integer()?.unwrap_or(0);
+        let length = match self.tokens.peek() {
+            match_kind!(ExpansionOperator) => {
+                self.tokens.next();
+                Some(self.parse_integer()?.unwrap_or(0))
+            }
+            _ => None,
+        };
+        self.expect(TokenKind::EndExpansion)?;
+        Ok(Substring::new(name, offset, length))
+    }
+
+    /// Parses an optional integer, surrounded by blanks.
+    fn parse_integer(&mut self) -> ParseResult<Option<i64>> {
+        if !matches!(self.tokens.peek(), match_kind!(Characters)) {
+            return Ok(None);
+        }
+        let token = self.take_cur()?;
+        match token.value.trim_matches([' ', '\t']) {
+            "" => Ok(None),
+            value => match value.parse() {
+                Ok(n) => Ok(Some(n)),
+                Err(_) => Err(ParseError::Unexpected(token)),
+            },
+        }
+    }
+
+    fn parse_filters(&mut self, name: String) -> ParseResult<Filtered> {
+        let mut filters = Vec::new();
+        while let match_kind!(Filter) = self.tokens.peek() {
+            let token = self.take_cur()?;
+            let filter = match token.value.split_once(':') {
+                Some((name, argument)) => Filter {
+                    name: name.to_owned(),
+                    argument: Some(argument.to_owned()),
+                },
+                None => Filter {
+                    name: token.value,
+                    argument: None,
+                },
+            };
+            filters.push(filter);
+        }
+        self.expect(TokenKind::EndExpansion)?;
+        Ok(Filtered::new(name, filters))
+    }
+
+    fn parse_replacement(&mut self, name: String, operator: &str) -> ParseResult<Replacement> {
+        let mode = match operator {
+            "//" => ReplacementMode::All,
+            "/#" => ReplacementMode::Prefix,
+            "/%" => ReplacementMode::Suffix,
+            _ => ReplacementMode::First,
+        };
+        let pattern = self.parse_words()?;
+        let replacement = match self.tokens.peek() {
+            match_kind!(ExpansionOperator) => {
+                self.tokens.next();
+                self.parse_words()?
+            }
+            _ => Vec::new(),
+        };
+        self.expect(TokenKind::EndExpansion)?;
+        Ok(Replacement::new(name, mode, pattern, replacement))
+    }
+
     fn expect(&mut self, kind: TokenKind) -> ParseResult<Token> {
         match self.tokens.next() {
             None => Err(ParseError::Eof),
@@ -171,3 +319,21 @@ where
         Err(ParseError::Syntax(self.tokens.next().unwrap().unwrap_err()))
     }
 }
+
+fn parse_operator(operator: &str) -> ParseResult<Operator> {
+    match operator {
+        "-" => Ok(Operator::IfUnset),
+        ":-" => Ok(Operator::IfUnsetOrNull),
+        "=" => Ok(Operator::AssignIfUnset),
+        ":=" => Ok(Operator::AssignIfUnsetOrNull),
+        "+" => Ok(Operator::IfSet),
+        ":+" => Ok(Operator::IfSetAndNotNull),
+        "?" => Ok(Operator::ErrorIfUnset),
+        ":?" => Ok(Operator::ErrorIfUnsetOrNull),
+        "#" => Ok(Operator::RemoveSmallestPrefix),
+        "##" => Ok(Operator::RemoveLargestPrefix),
+        "%" => Ok(Operator::RemoveSmallestSuffix),
+        "%%" => Ok(Operator::RemoveLargestSuffix),
+        op => Err(ParseError::UnknownOperator(op.to_owned())),
+    }
+}
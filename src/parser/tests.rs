@@ -2,8 +2,16 @@
 
 use rstest::rstest;
 
-use super::{ast::Assignment, ParseResult, Parser};
+use super::{
+    ast::{
+        Assignment, Expansion, Expression, Filter, Filtered, Length, Operator, Replacement,
+        ReplacementMode, Resolved, Substring, Tilde,
+    },
+    parse_template, parse_value, parse_with_options, ParseResult, Parser,
+};
 use crate::{
+    dialect::Dialect,
+    options::Options,
     parser::ParseError,
     tokenizer::{
         err::{ErrorKind, SyntaxError},
@@ -84,6 +92,13 @@ macro_rules! syn_err {
     ],
     |r| assert!(matches!(r, Err(ParseError::Unexpected(_)))),
 )]
+#[case::unexpected_after_export(
+    vec![
+        tok!(Export, "export"),
+        tok!(Characters, "foo"),
+    ],
+    |r| assert!(matches!(r, Err(ParseError::Unexpected(_)))),
+)]
 #[case::unknown_operator(
     vec![
         tok!(Assign, "foo"),
@@ -117,3 +132,236 @@ fn parse_errors(
     let res = Parser::new(input.into_iter()).parse();
     assert(res);
 }
+
+#[test]
+fn parse_export_prefix() {
+    let input = vec![
+        tok!(Export, "export"),
+        tok!(Assign, "foo"),
+        tok!(Characters, "bar"),
+        tok!(Assign, "baz"),
+        tok!(Eof, ""),
+    ];
+    let ast = Parser::new(input.into_iter()).parse().unwrap();
+    assert!(ast[0].export);
+    assert!(!ast[1].export);
+}
+
+#[test]
+fn parse_positions() {
+    let input = vec![
+        tok!(Export, "export", 1, 1),
+        tok!(Assign, "foo", 1, 8),
+        tok!(StartExpansion, "bar", 1, 14),
+        tok!(ExpansionOperator, "-"),
+        tok!(SimpleExpansion, "baz", 1, 19),
+        tok!(EndExpansion, "}"),
+        tok!(Eof, ""),
+    ];
+    let ast = Parser::new(input.into_iter()).parse().unwrap();
+    assert_eq!(Position::new(1, 8), ast[0].position);
+    let Expression::Expansion(bar) = &ast[0].value[0] else {
+        panic!("Expected an expansion, got {:?}", ast[0].value[0]);
+    };
+    assert_eq!(Position::new(1, 14), bar.position);
+    let Expression::Expansion(baz) = &bar.rhs[0] else {
+        panic!("Expected an expansion, got {:?}", bar.rhs[0]);
+    };
+    assert_eq!(Position::new(1, 19), baz.position);
+}
+
+#[test]
+fn parse_string_length() {
+    let input = vec![
+        tok!(Assign, "foo", 1, 1),
+        tok!(StringLength, "bar", 1, 7),
+        tok!(Eof, ""),
+    ];
+    let ast = Parser::new(input.into_iter()).parse().unwrap();
+    let expected = Length::new("bar".into()).at(Position::new(1, 7));
+    assert_eq!(vec![Expression::Length(expected)], ast[0].value);
+}
+
+#[rstest]
+#[case("#", Operator::RemoveSmallestPrefix)]
+#[case("##", Operator::RemoveLargestPrefix)]
+#[case("%", Operator::RemoveSmallestSuffix)]
+#[case("%%", Operator::RemoveLargestSuffix)]
+fn parse_pattern_operators(#[case] operator: &'static str, #[case] expected: Operator) {
+    let input = vec![
+        tok!(Assign, "foo"),
+        Ok(Token::new(
+            TokenKind::StartExpansion,
+            "bar".into(),
+            Position::new(0, 0),
+        )),
+        Ok(Token::new(
+            TokenKind::ExpansionOperator,
+            operator.into(),
+            Position::new(0, 0),
+        )),
+        tok!(Characters, "*/"),
+        tok!(EndExpansion, "}"),
+        tok!(Eof, ""),
+    ];
+    let ast = Parser::new(input.into_iter()).parse().unwrap();
+    let expected = Expansion::new(
+        "bar".into(),
+        expected,
+        vec![Expression::Characters("*/".into())],
+    );
+    assert_eq!(vec![Expression::Expansion(expected)], ast[0].value);
+}
+
+fn parse_bash(input: &str) -> ParseResult<Vec<Assignment>> {
+    let options = Options {
+        bash_expansions: true,
+        ..Default::default()
+    };
+    parse_with_options(input, None, options)
+}
+
+#[rstest]
+#[case::offset("A=${B:2}", 2, None)]
+#[case::length("A=${B:2:3}", 2, Some(3))]
+#[case::negative("A=${B: -2: -1}", -2, Some(-1))]
+#[case::empty("A=${B: :}", 0, Some(0))]
+fn parse_substring(#[case] input: &str, #[case] offset: i64, #[case] length: Option<i64>) {
+    let ast = parse_bash(input).unwrap();
+    let expected = Substring::new("B".into(), offset, length).at(Position::new(1, 5));
+    assert_eq!(vec![Expression::Substring(expected)], ast[0].value);
+}
+
+#[rstest]
+#[case::minus_only("A=${B: -}")]
+#[case::inner_blank("A=${B:1 2}")]
+#[case::inner_minus("A=${B:1-2}")]
+fn parse_invalid_substring(#[case] input: &str) {
+    let err = parse_bash(input).unwrap_err();
+    assert!(matches!(err, ParseError::Unexpected(_)), "{err:?}");
+}
+
+#[rstest]
+#[case::first("A=${B/x/y}", ReplacementMode::First, Some("y"))]
+#[case::all("A=${B//x/y}", ReplacementMode::All, Some("y"))]
+#[case::prefix("A=${B/#x/y}", ReplacementMode::Prefix, Some("y"))]
+#[case::suffix("A=${B/%x/y}", ReplacementMode::Suffix, Some("y"))]
+#[case::no_replacement("A=${B/x}", ReplacementMode::First, None)]
+fn parse_replacement(
+    #[case] input: &str,
+    #[case] mode: ReplacementMode,
+    #[case] replacement: Option<&str>,
+) {
+    let ast = parse_bash(input).unwrap();
+    let pattern = vec![Expression::Characters("x".into())];
+    let replacement = replacement
+        .map(|r| vec![Expression::Characters(r.into())])
+        .unwrap_or_default();
+    let expected = Replacement::new("B".into(), mode, pattern, replacement).at(Position::new(1, 5));
+    assert_eq!(vec![Expression::Replacement(expected)], ast[0].value);
+}
+
+#[rstest]
+#[case::quoted_pattern("A=${B/\"$C${D}\"/$E}", vec![true, true], false)]
+#[case::partly_quoted_pattern("A=${B/\"$C\"${D}/$E}", vec![true, false], false)]
+#[case::unquoted_pattern("A=${B/$C\"${D}\"/\"$E\"}", vec![false, true], false)]
+#[case::quoted_expansion("A=\"${B/$C${D}/$E}\"", vec![false, false], false)]
+fn parse_quoted_pattern_expansions(
+    #[case] input: &str,
+    #[case] pattern: Vec<bool>,
+    #[case] replacement: bool,
+) {
+    let ast = parse_bash(input).unwrap();
+    let Expression::Replacement(node) = &ast[0].value[0] else {
+        panic!("Unexpected node {:?}", ast[0].value[0]);
+    };
+    let is_quoted = |expr: &Expression| matches!(expr, Expression::Quoted(_));
+    let quoted: Vec<bool> = node.pattern.iter().map(is_quoted).collect();
+    assert_eq!(pattern, quoted);
+    assert_eq!(replacement, is_quoted(&node.replacement[0]));
+}
+
+#[test]
+fn parse_filters() {
+    let options = Options {
+        filters: true,
+        ..Default::default()
+    };
+    let ast = parse_with_options("A=${B|split:,|first}", None, options).unwrap();
+    let filters = vec![
+        Filter {
+            name: "split".into(),
+            argument: Some(",".into()),
+        },
+        Filter {
+            name: "first".into(),
+            argument: None,
+        },
+    ];
+    let expected = Filtered::new("B".into(), filters).at(Position::new(1, 5));
+    assert_eq!(vec![Expression::Filtered(expected)], ast[0].value);
+}
+
+#[test]
+fn parse_resolved() {
+    let options = Options {
+        resolvers: true,
+        ..Default::default()
+    };
+    let ast = parse_with_options("A=${file:$DIR/db}", None, options).unwrap();
+    let argument = vec![
+        Expression::Expansion(Expansion::plain("DIR".into()).at(Position::new(1, 11))),
+        Expression::Characters("/db".into()),
+    ];
+    let expected = Resolved::new("file".into(), argument).at(Position::new(1, 5));
+    assert_eq!(vec![Expression::Resolved(expected)], ast[0].value);
+}
+
+#[test]
+fn parse_tilde() {
+    let options = Options {
+        tilde_expansion: true,
+        ..Default::default()
+    };
+    let ast = parse_with_options("A=~/bin:~user", None, options).unwrap();
+    let expected = vec![
+        Expression::Tilde(Tilde::new("".into()).at(Position::new(1, 3))),
+        Expression::Characters("/bin:".into()),
+        Expression::Tilde(Tilde::new("user".into()).at(Position::new(1, 9))),
+    ];
+    assert_eq!(expected, ast[0].value);
+}
+
+#[test]
+fn parse_single_value() {
+    let ast = parse_value("'a b'$C # comment\n", Options::default()).unwrap();
+    let expected = vec![
+        Expression::Characters("a b".into()),
+        Expression::Expansion(Expansion::plain("C".into()).at(Position::new(1, 7))),
+    ];
+    assert_eq!(expected, ast);
+    assert_eq!(
+        Vec::<Expression>::new(),
+        parse_value("", Options::default()).unwrap()
+    );
+}
+
+#[rstest]
+#[case::unquoted_blank("a b")]
+#[case::assignment("a\nB=b")]
+#[case::unterminated_quote("'a")]
+#[case::special_character("a|b")]
+fn parse_value_errors(#[case] input: &str) {
+    assert!(parse_value(input, Options::default()).is_err());
+}
+
+#[test]
+fn parse_value_unsupported_dialect() {
+    let options = Options {
+        dialect: Dialect::Node,
+        ..Options::default()
+    };
+    let err = || Err(ParseError::UnsupportedDialect(Dialect::Node));
+    assert_eq!(err(), parse_value("a", options));
+    assert_eq!(err(), parse_template("a", options));
+}
@@ -0,0 +1,39 @@
+//! Helpers for running commands created by [Potenv::command](crate::Potenv::command).
+
+use std::{
+    io,
+    process::{Command, ExitStatus},
+};
+
+/// Returns the exit code a shell would report for the given exit status.
+///
+/// On Unix, a process terminated by a signal gets an exit code of 128 plus the signal number.
+pub fn exit_code(status: ExitStatus) -> i32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    status.code().unwrap_or(1)
+}
+
+/// Runs the command in place of the current process.
+///
+/// On Unix, the current process image is replaced by the command,
+/// so that its exit code and signals are those of the command,
+/// and this function only returns on error.
+///
+/// On other platforms, the command is run to completion and its exit code is returned.
+pub fn exec(cmd: &mut Command) -> io::Result<i32> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        Err(cmd.exec())
+    }
+    #[cfg(not(unix))]
+    {
+        cmd.status().map(exit_code)
+    }
+}
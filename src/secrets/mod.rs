@@ -0,0 +1,147 @@
+//! Support for the `_FILE` convention of Docker and Kubernetes secrets,
+//! where `DB_PASSWORD_FILE=/run/secrets/db` defines `DB_PASSWORD` from the content of a file.
+//!
+//! ```no_run
+//! use potenv::{evaluator::Scope, secrets::FileSecrets};
+//!
+//! let mut scope = Scope::from([("DB_PASSWORD_FILE".to_owned(), "/run/secrets/db".to_owned())]);
+//! let secrets = FileSecrets::new().max_size(4096).apply(&mut scope).unwrap();
+//! assert_eq!("DB_PASSWORD", secrets[0].name);
+//! assert!(scope.contains_key("DB_PASSWORD"));
+//! ```
+
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::PathBuf,
+};
+
+use thiserror::Error;
+
+use crate::evaluator::Scope;
+
+#[cfg(test)]
+mod tests;
+
+/// The suffix of the variables holding the path of a secret file.
+pub const SUFFIX: &str = "_FILE";
+
+/// The default maximum size of a secret file, in bytes.
+pub const DEFAULT_MAX_SIZE: u64 = 64 * 1024;
+
+#[derive(Debug, Error)]
+pub enum SecretFileError {
+    #[error("Variable ${0} conflicts with ${0}{SUFFIX}")]
+    Conflict(String),
+    #[error("Could not read {path:?} for variable ${variable}: {source}")]
+    Io {
+        variable: String,
+        path: PathBuf,
+        source: io::Error,
+    },
+    #[error("File {path:?} for variable ${variable} exceeds the maximum size of {max_size} bytes")]
+    TooLarge {
+        variable: String,
+        path: PathBuf,
+        max_size: u64,
+    },
+    #[error("File {path:?} for variable ${variable} is not valid UTF-8")]
+    InvalidUtf8 { variable: String, path: PathBuf },
+}
+
+/// The provenance of a variable defined from a secret file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretFile {
+    /// The name of the defined variable.
+    pub name: String,
+    /// The name of the variable holding the path, i.e. `name` followed by `_FILE`.
+    pub variable: String,
+    pub path: PathBuf,
+    /// The size of the file, in bytes.
+    pub size: u64,
+}
+
+/// A pass defining variables from the files referenced by `_FILE` variables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileSecrets {
+    max_size: u64,
+}
+
+impl Default for FileSecrets {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FileSecrets {
+    pub fn new() -> Self {
+        Self {
+            max_size: DEFAULT_MAX_SIZE,
+        }
+    }
+
+    /// Sets the maximum size of a secret file, in bytes.
+    /// Defaults to [DEFAULT_MAX_SIZE].
+    pub fn max_size(mut self, max_size: u64) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// For every `X_FILE` variable of the scope, defines `X` from the content of the file,
+    /// without its trailing newlines as in a shell command substitution.
+    /// `X_FILE` is kept in the scope, and `X` must not already be defined.
+    ///
+    /// Returns the provenance of the defined variables, in declaration order.
+    pub fn apply(&self, scope: &mut Scope) -> Result<Vec<SecretFile>, SecretFileError> {
+        let mut secrets = Vec::new();
+        for (variable, path) in scope.iter() {
+            let Some(name) = variable.strip_suffix(SUFFIX).filter(|n| !n.is_empty()) else {
+                continue;
+            };
+            if scope.contains_key(name) {
+                return Err(SecretFileError::Conflict(name.to_owned()));
+            }
+            secrets.push(SecretFile {
+                name: name.to_owned(),
+                variable: variable.clone(),
+                path: PathBuf::from(path),
+                size: 0,
+            });
+        }
+        for secret in &mut secrets {
+            let value = self.read(secret)?;
+            scope.insert(secret.name.clone(), value);
+        }
+        Ok(secrets)
+    }
+
+    /// Reads a secret file, recording its size.
+    fn read(&self, secret: &mut SecretFile) -> Result<String, SecretFileError> {
+        let io_error = |source| SecretFileError::Io {
+            variable: secret.variable.clone(),
+            path: secret.path.clone(),
+            source,
+        };
+        let mut bytes = Vec::new();
+        File::open(&secret.path)
+            .and_then(|file| {
+                file.take(self.max_size.saturating_add(1))
+                    .read_to_end(&mut bytes)
+            })
+            .map_err(io_error)?;
+        secret.size = bytes.len() as u64;
+        if secret.size > self.max_size {
+            return Err(SecretFileError::TooLarge {
+                variable: secret.variable.clone(),
+                path: secret.path.clone(),
+                max_size: self.max_size,
+            });
+        }
+        let mut value = String::from_utf8(bytes).map_err(|_| SecretFileError::InvalidUtf8 {
+            variable: secret.variable.clone(),
+            path: secret.path.clone(),
+        })?;
+        value.truncate(value.trim_end_matches('\n').len());
+        Ok(value)
+    }
+}
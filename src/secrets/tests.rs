@@ -0,0 +1,87 @@
+use std::path::PathBuf;
+
+use rstest::rstest;
+
+use super::{FileSecrets, SecretFile, SecretFileError};
+use crate::evaluator::Scope;
+
+fn secret_path(name: &str) -> String {
+    let project_dir =
+        std::env::var("CARGO_MANIFEST_DIR").expect("Could not determine project directory.");
+    PathBuf::from(project_dir)
+        .join("tests/resources/secrets")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn scope(vars: &[(&str, &str)]) -> Scope {
+    vars.iter()
+        .map(|(name, file)| (name.to_string(), secret_path(file)))
+        .collect()
+}
+
+#[test]
+fn test_apply() {
+    let mut scope = scope(&[
+        ("DB_PASSWORD_FILE", "db_password"),
+        ("DB_USER_FILE", "db_user"),
+    ]);
+    scope.insert("_FILE".to_owned(), "not a secret".to_owned());
+    let secrets = FileSecrets::new().apply(&mut scope).unwrap();
+    assert_eq!("s3cr3t", scope["DB_PASSWORD"]);
+    assert_eq!("admin", scope["DB_USER"]);
+    assert_eq!(secret_path("db_user"), scope["DB_USER_FILE"]);
+    assert_eq!(
+        vec![
+            SecretFile {
+                name: "DB_PASSWORD".to_owned(),
+                variable: "DB_PASSWORD_FILE".to_owned(),
+                path: secret_path("db_password").into(),
+                size: 8,
+            },
+            SecretFile {
+                name: "DB_USER".to_owned(),
+                variable: "DB_USER_FILE".to_owned(),
+                path: secret_path("db_user").into(),
+                size: 5,
+            },
+        ],
+        secrets
+    );
+}
+
+#[rstest]
+#[case::conflict(&[("A", "db_user"), ("A_FILE", "db_user")], 100, |err: &SecretFileError| matches!(err, SecretFileError::Conflict(name) if name == "A"))]
+#[case::missing(&[("A_FILE", "missing")], 100, |err: &SecretFileError| matches!(err, SecretFileError::Io { .. }))]
+#[case::too_large(&[("A_FILE", "db_password")], 7, |err: &SecretFileError| matches!(err, SecretFileError::TooLarge { max_size: 7, .. }))]
+#[case::invalid_utf8(&[("A_FILE", "binary")], 100, |err: &SecretFileError| matches!(err, SecretFileError::InvalidUtf8 { .. }))]
+fn test_errors(
+    #[case] vars: &[(&str, &str)],
+    #[case] max_size: u64,
+    #[case] expected: fn(&SecretFileError) -> bool,
+) {
+    let mut scope = scope(vars);
+    let err = FileSecrets::new()
+        .max_size(max_size)
+        .apply(&mut scope)
+        .unwrap_err();
+    assert!(expected(&err), "{err:?}");
+}
+
+#[test]
+fn test_exact_max_size() {
+    let mut scope = scope(&[("A_FILE", "db_password")]);
+    FileSecrets::new().max_size(8).apply(&mut scope).unwrap();
+    assert_eq!("s3cr3t", scope["A"]);
+}
+
+#[test]
+fn test_unlimited_max_size() {
+    let mut scope = scope(&[("A_FILE", "db_password")]);
+    FileSecrets::new()
+        .max_size(u64::MAX)
+        .apply(&mut scope)
+        .unwrap();
+    assert_eq!("s3cr3t", scope["A"]);
+}
@@ -0,0 +1,117 @@
+//! Templates where expansions are substituted, as with `envsubst`,
+//! e.g. to render configuration files from the evaluated variables.
+//!
+//! ```
+//! use potenv::{evaluator::Scope, template::Template};
+//! use std::collections::HashMap;
+//!
+//! let scope = Scope::from([("HOST".to_owned(), "example.com".to_owned())]);
+//! let template = Template::new("server_name ${HOST}:${PORT:-80} $host;")
+//!     .unwrap()
+//!     .keep_unknown(true);
+//! let output = template.render(&scope, &HashMap::new()).unwrap();
+//! assert_eq!("server_name example.com:80 $host;", output);
+//! ```
+
+use crate::{
+    env::EnvProvider,
+    evaluator::{EvaluationError, Evaluator, Scope},
+    options::Options,
+    parser::{
+        ast::{Expansion, Expression},
+        parse_template, ParseResult,
+    },
+};
+
+#[cfg(test)]
+mod tests;
+
+/// A parsed template. Only expansions are recognized, everything else is kept literally.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    source: String,
+    nodes: Vec<Expression>,
+    keep_unknown: bool,
+}
+
+impl Template {
+    pub fn new(source: impl Into<String>) -> ParseResult<Self> {
+        Self::with_options(source, Options::default())
+    }
+
+    /// Parses a template, accepting the expansions enabled by the options.
+    pub fn with_options(source: impl Into<String>, options: Options) -> ParseResult<Self> {
+        let source = source.into();
+        let nodes = parse_template(&source, options)?;
+        Ok(Self {
+            source,
+            nodes,
+            keep_unknown: false,
+        })
+    }
+
+    /// Sets whether `$NAME` and `${NAME}` expansions of undefined variables are kept as written,
+    /// instead of expanding to an empty string. Expansions with an operator are always evaluated.
+    /// Defaults to false.
+    pub fn keep_unknown(mut self, keep_unknown: bool) -> Self {
+        self.keep_unknown = keep_unknown;
+        self
+    }
+
+    /// Renders the template, looking up variables in the scope, then in the environment,
+    /// like [crate::evaluate_value].
+    pub fn render<T: EnvProvider>(
+        &self,
+        scope: &Scope,
+        env: &T,
+    ) -> Result<String, EvaluationError> {
+        self.render_with(Evaluator::new(env, true).scope(scope.clone()))
+    }
+
+    /// Renders the template with an evaluator, e.g. to use custom filters or resolvers.
+    pub fn render_with<T: EnvProvider>(
+        &self,
+        mut evaluator: Evaluator<'_, T>,
+    ) -> Result<String, EvaluationError> {
+        let mut output = String::with_capacity(self.source.len());
+        // Nodes are evaluated one at a time, so that variables assigned by `${NAME:=word}`
+        // are known to the expansions that follow.
+        for node in &self.nodes {
+            let mut node = [node.clone()];
+            if self.keep_unknown {
+                self.keep_undefined(&mut node, &evaluator);
+            }
+            output.push_str(&evaluator.evaluate_value(node.into())?);
+        }
+        Ok(output)
+    }
+
+    /// Replaces the plain expansions of undefined variables by their source.
+    fn keep_undefined<T: EnvProvider>(&self, nodes: &mut [Expression], evaluator: &Evaluator<T>) {
+        for node in nodes {
+            let expansion = match node {
+                Expression::Expansion(expansion) => expansion,
+                Expression::Quoted(quoted) => {
+                    self.keep_undefined(std::slice::from_mut(&mut **quoted), evaluator);
+                    continue;
+                }
+                _ => continue,
+            };
+            if !expansion.plain {
+                self.keep_undefined(&mut expansion.rhs, evaluator);
+            } else if evaluator.resolve(&expansion.name).is_none() {
+                *node = Expression::Characters(self.plain_source(expansion).to_owned());
+            }
+        }
+    }
+
+    /// Returns the source of a `$NAME` or `${NAME}` expansion.
+    fn plain_source(&self, expansion: &Expansion) -> &str {
+        let start = expansion.position.offset(&self.source);
+        let end = start + expansion.name.len();
+        match self.source[..start].ends_with("${") {
+            true => &self.source[start - 2..end + 1],
+            false => &self.source[start - 1..end],
+        }
+    }
+}
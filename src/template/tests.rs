@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use rstest::rstest;
+
+use super::Template;
+use crate::{
+    evaluator::{EvaluationError, Evaluator, Scope},
+    options::Options,
+    parser::ParseError,
+};
+
+fn render(source: &str, keep_unknown: bool) -> Result<String, EvaluationError> {
+    let scope = Scope::from([
+        ("HOST".to_owned(), "example.com".to_owned()),
+        ("EMPTY".to_owned(), "".to_owned()),
+    ]);
+    let env = HashMap::from([("PORT".to_owned(), "8080".to_owned())]);
+    Template::new(source)
+        .unwrap()
+        .keep_unknown(keep_unknown)
+        .render(&scope, &env)
+}
+
+#[rstest]
+#[case::text("listen 80;\n", "listen 80;\n")]
+#[case::simple("host $HOST;", "host example.com;")]
+#[case::braced("${HOST}:${PORT}", "example.com:8080")]
+#[case::default("${WORKERS:-4}", "4")]
+#[case::nested_default("${WORKERS:-${PORT}}", "8080")]
+#[case::quotes(
+    r#"{"host": "$HOST", 'port': $PORT}"#,
+    r#"{"host": "example.com", 'port': 8080}"#
+)]
+#[case::quoted_default("${WORKERS:-'a  b'}", "a  b")]
+#[case::backslash(r"C:\$HOST\n", r"C:\example.com\n")]
+#[case::dollar("cost: 5$ $", "cost: 5$ $")]
+#[case::special_parameters("$1 $@ $$HOST $(pwd)", "$1 $@ $example.com $(pwd)")]
+#[case::undefined("[$host]", "[]")]
+#[case::multiline("a\n\n  $HOST\n", "a\n\n  example.com\n")]
+fn test_render(#[case] source: &str, #[case] expected: &str) {
+    assert_eq!(expected, render(source, false).unwrap());
+}
+
+#[rstest]
+#[case::simple("$host $HOST", "$host example.com")]
+#[case::braced("${host}:${PORT}", "${host}:8080")]
+#[case::empty_default("[${host-}]", "[]")]
+#[case::default("${host:-localhost}", "localhost")]
+#[case::nested_default("${A:-$host}", "$host")]
+#[case::empty_value("[$EMPTY]", "[]")]
+#[case::assigned_before("${host:=localhost} $host ${host}", "localhost localhost localhost")]
+#[case::assigned_after("$host ${host:=localhost}", "$host localhost")]
+#[case::multiline("a\n  ${host} $remote_addr\n", "a\n  ${host} $remote_addr\n")]
+fn test_keep_unknown(#[case] source: &str, #[case] expected: &str) {
+    assert_eq!(expected, render(source, true).unwrap());
+}
+
+#[test]
+fn test_required_variable() {
+    let err = render("${DB_HOST:?is required}", true).unwrap_err();
+    assert!(matches!(err, EvaluationError::UndefinedVariable(name, _) if name == "DB_HOST"));
+}
+
+#[rstest]
+#[case::unterminated("${HOST")]
+#[case::invalid_name("${-}")]
+fn test_syntax_errors(#[case] source: &str) {
+    assert!(matches!(Template::new(source), Err(ParseError::Syntax(_))));
+}
+
+#[test]
+fn test_options() {
+    let options = Options {
+        string_length: true,
+        ..Options::default()
+    };
+    let template = Template::with_options("${#HOST}", options).unwrap();
+    let scope = Scope::from([("HOST".to_owned(), "example.com".to_owned())]);
+    assert_eq!("11", template.render(&scope, &HashMap::new()).unwrap());
+}
+
+#[test]
+fn test_render_with() {
+    let env = HashMap::from([("HOST".to_owned(), "env.example.com".to_owned())]);
+    let scope = Scope::from([("HOST".to_owned(), "example.com".to_owned())]);
+    let template = Template::new("$HOST").unwrap();
+    let evaluator = Evaluator::new(&env, false).scope(scope);
+    assert_eq!("env.example.com", template.render_with(evaluator).unwrap());
+}
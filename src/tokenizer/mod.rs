@@ -5,17 +5,25 @@ use self::{
     pos::Position,
     token::*,
 };
+use crate::options::Options;
 
 pub mod err;
 pub mod pos;
 #[cfg(test)]
+#[allow(
+    clippy::needless_return,
+    clippy::to_string_trait_impl,
+    clippy::useless_conversion
+)]
 mod tests;
 pub mod token;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum State {
+pub(crate) enum State {
     AssignmentList,
+    Template,
     Comment,
+    ExportPrefix,
     AssignmentName,
     AssignmentValue,
     AssignmentValueEscape,
@@ -25,16 +33,52 @@ enum State {
     Dollar,
     SimpleExpansion,
     ComplexExpansionStart,
+    StringLengthStart,
+    StringLength,
     ComplexExpansion,
     ExpansionOperator,
+    PatternOperator,
+    SubstringOffset,
+    SubstringLength,
+    ReplacementOperator,
+    ReplacementSeparator,
+    FilterName,
+    FilterArgument,
+    TildePrefix,
     ExpansionValue,
     ExpansionValueEscape,
 }
 
+/// How the value of an open expansion is tokenized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ValueKind {
+    /// A word, as in `${NAME:-word}`.
+    Word,
+    /// A pattern, as in `${NAME#pattern}`.
+    Pattern,
+    /// A pattern ended by a `/`, as in `${NAME/pattern/replacement}`.
+    ReplacementPattern,
+}
+
 pub type TokenizerResult = Result<Token, SyntaxError>;
 
+/// A character consumed by the tokenizer, along with the state that consumed it.
+/// For reconsumed characters, this is the last state that consumed them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TraceEntry {
+    /// The byte offset of the character in the input.
+    pub offset: usize,
+    pub ch: char,
+    pub state: State,
+}
+
 #[inline(always)]
-fn is_wsnl(ch: char) -> bool {
+pub(crate) fn is_blank(ch: char) -> bool {
+    matches!(ch, ' ' | '\t')
+}
+
+#[inline(always)]
+pub(crate) fn is_wsnl(ch: char) -> bool {
     matches!(ch, ' ' | '\t' | '\n')
 }
 
@@ -48,6 +92,12 @@ fn is_identifier_char(ch: char) -> bool {
     ch.is_ascii_alphanumeric() || ch == '_'
 }
 
+/// Returns whether the given string is a valid variable name.
+pub(crate) fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(is_identifier_start) && chars.all(is_identifier_char)
+}
+
 #[inline(always)]
 fn is_shell_special_char(ch: char) -> bool {
     matches!(ch, '|' | '&' | ';' | '<' | '>' | '(' | ')')
@@ -59,7 +109,7 @@ fn is_shell_special_param(ch: char) -> bool {
 }
 
 #[inline(always)]
-fn is_dq_escape(ch: char) -> bool {
+pub(crate) fn is_dq_escape(ch: char) -> bool {
     matches!(ch, '"' | '$' | '`' | '\\')
 }
 
@@ -68,6 +118,16 @@ fn is_operator(ch: char) -> bool {
     matches!(ch, '-' | '=' | '+' | '?')
 }
 
+#[inline(always)]
+fn is_pattern_operator(ch: char) -> bool {
+    matches!(ch, '#' | '%')
+}
+
+#[inline(always)]
+fn is_pattern_special_char(ch: char) -> bool {
+    matches!(ch, '*' | '?' | '[' | '\\')
+}
+
 #[derive(Debug)]
 pub struct Tokenizer<I>
 where
@@ -75,6 +135,7 @@ where
 {
     input: I,
     filename: Option<PathBuf>,
+    options: Options,
     done: bool,
     state: State,
     return_states: VecDeque<State>,
@@ -85,9 +146,16 @@ where
     reconsume: bool,
     line: usize,
     column: usize,
+    offset: usize,
+    trace: Option<Vec<TraceEntry>>,
     single_quote_pos: Position,
     quoting_stack: VecDeque<Position>,
-    expansion_stack: VecDeque<Position>,
+    /// Whether the next character of an assignment value can start a tilde-prefix.
+    tilde_allowed: bool,
+    /// The positions of the open expansions, and how their value is tokenized.
+    expansion_stack: VecDeque<(Position, ValueKind)>,
+    /// Whether the expansion being tokenized is double-quoted inside a pattern.
+    quoted_expansion: bool,
 }
 
 impl<I> Iterator for Tokenizer<I>
@@ -119,9 +187,14 @@ where
     I: Iterator<Item = char>,
 {
     pub fn new(input: I, filename: Option<PathBuf>) -> Self {
+        Self::with_options(input, filename, Options::default())
+    }
+
+    pub fn with_options(input: I, filename: Option<PathBuf>, options: Options) -> Self {
         Self {
             input,
             filename,
+            options,
             done: false,
             state: State::AssignmentList,
             return_states: VecDeque::with_capacity(16),
@@ -132,12 +205,39 @@ where
             cc: None,
             line: 1,
             column: 0,
+            offset: 0,
+            trace: None,
             single_quote_pos: Position::new(0, 0),
             quoting_stack: VecDeque::with_capacity(8),
+            tilde_allowed: false,
             expansion_stack: VecDeque::with_capacity(8),
+            quoted_expansion: false,
         }
     }
 
+    /// Tokenizes the input as a template, i.e. text where only expansions are recognized.
+    pub(crate) fn template(mut self) -> Self {
+        self.state = State::Template;
+        self
+    }
+
+    /// Tokenizes the input as a single value, i.e. the right-hand side of an assignment.
+    pub(crate) fn value(mut self) -> Self {
+        self.state = State::AssignmentValue;
+        self.tilde_allowed = self.options.tilde_expansion;
+        self
+    }
+
+    /// Records every consumed character, to be retrieved with [Tokenizer::take_trace].
+    pub(crate) fn traced(mut self) -> Self {
+        self.trace = Some(Vec::new());
+        self
+    }
+
+    pub(crate) fn take_trace(&mut self) -> Vec<TraceEntry> {
+        self.trace.take().unwrap_or_default()
+    }
+
     #[allow(clippy::unit_arg)]
     fn run(&mut self) -> Result<(), SyntaxError> {
         match self.state {
@@ -152,6 +252,20 @@ where
                 }
                 Some(c) => self.err(ErrorKind::InvalidCharacter(c)),
             },
+            State::Template => loop {
+                match self.consume_the_next_character() {
+                    None => {
+                        self.flush_buffer(TokenKind::Characters);
+                        return Ok(self.emit_eof());
+                    }
+                    Some('\0') => return self.err(ErrorKind::NullCharacter),
+                    Some('$') => {
+                        self.return_states.push_back(self.state);
+                        return Ok(self.switch_to(State::Dollar));
+                    }
+                    Some(c) => self.buffer(c),
+                };
+            },
             State::Comment => loop {
                 match self.consume_the_next_character() {
                     None => return Ok(self.emit_eof()),
@@ -160,50 +274,77 @@ where
                     Some(_) => (),
                 };
             },
+            State::ExportPrefix => match self.consume_the_next_character() {
+                None => self.err_eof(),
+                Some('\0') => self.err(ErrorKind::NullCharacter),
+                Some(c) if is_blank(c) => Ok(()),
+                Some(c) if is_identifier_start(c) => {
+                    self.buffer(c);
+                    Ok(self.switch_to(State::AssignmentName))
+                }
+                Some(c) => self.err(ErrorKind::InvalidCharacter(c)),
+            },
             State::AssignmentName => match self.consume_the_next_character() {
                 None => self.err_eof(),
                 Some('\0') => self.err(ErrorKind::NullCharacter),
                 Some('=') => {
                     self.flush_buffer(TokenKind::Assign);
+                    self.tilde_allowed = self.options.tilde_expansion;
                     Ok(self.switch_to(State::AssignmentValue))
                 }
+                Some(c) if is_blank(c) && self.options.export_prefix && self.buf == "export" => {
+                    self.flush_buffer(TokenKind::Export);
+                    Ok(self.switch_to(State::ExportPrefix))
+                }
                 Some(c) if is_identifier_char(c) => {
                     self.buffer(c);
                     Ok(())
                 }
                 Some(c) => self.err(ErrorKind::InvalidCharacter(c)),
             },
-            State::AssignmentValue => match self.consume_the_next_character() {
-                None => {
-                    self.flush_buffer(TokenKind::Characters);
-                    Ok(self.emit_eof())
-                }
-                Some('\0') => self.err(ErrorKind::NullCharacter),
-                Some(c) if is_wsnl(c) => {
-                    self.flush_buffer(TokenKind::Characters);
-                    Ok(self.switch_to(State::AssignmentList))
-                }
-                Some('\\') => Ok(self.switch_to(State::AssignmentValueEscape)),
-                Some('\'') => {
-                    self.single_quote_pos = self.cur_pos();
-                    self.return_states.push_back(self.state);
-                    Ok(self.switch_to(State::SingleQuoted))
-                }
-                Some('"') => {
-                    self.quoting_stack.push_back(self.cur_pos());
-                    self.return_states.push_back(self.state);
-                    Ok(self.switch_to(State::DoubleQuoted))
-                }
-                Some('$') => {
-                    self.return_states.push_back(self.state);
-                    Ok(self.switch_to(State::Dollar))
-                }
-                Some('`') => self.err(ErrorKind::UnsupportedCommandExpansion),
-                Some(c) if is_shell_special_char(c) => {
-                    self.err(ErrorKind::UnescapedSpecialCharacter(c))
+            State::AssignmentValue => {
+                let tilde_allowed = std::mem::take(&mut self.tilde_allowed);
+                match self.consume_the_next_character() {
+                    None => {
+                        self.flush_buffer(TokenKind::Characters);
+                        Ok(self.emit_eof())
+                    }
+                    Some('~') if tilde_allowed => {
+                        self.flush_buffer(TokenKind::Characters);
+                        Ok(self.reconsume_in(State::TildePrefix))
+                    }
+                    Some(':') if self.options.tilde_expansion => {
+                        self.buffer(':');
+                        self.tilde_allowed = true;
+                        Ok(())
+                    }
+                    Some('\0') => self.err(ErrorKind::NullCharacter),
+                    Some(c) if is_wsnl(c) => {
+                        self.flush_buffer(TokenKind::Characters);
+                        Ok(self.switch_to(State::AssignmentList))
+                    }
+                    Some('\\') => Ok(self.switch_to(State::AssignmentValueEscape)),
+                    Some('\'') => {
+                        self.single_quote_pos = self.cur_pos();
+                        self.return_states.push_back(self.state);
+                        Ok(self.switch_to(State::SingleQuoted))
+                    }
+                    Some('"') => {
+                        self.quoting_stack.push_back(self.cur_pos());
+                        self.return_states.push_back(self.state);
+                        Ok(self.switch_to(State::DoubleQuoted))
+                    }
+                    Some('$') => {
+                        self.return_states.push_back(self.state);
+                        Ok(self.switch_to(State::Dollar))
+                    }
+                    Some('`') => self.err(ErrorKind::UnsupportedCommandExpansion),
+                    Some(c) if is_shell_special_char(c) => {
+                        self.err(ErrorKind::UnescapedSpecialCharacter(c))
+                    }
+                    Some(c) => Ok(self.buffer(c)),
                 }
-                Some(c) => Ok(self.buffer(c)),
-            },
+            }
             State::AssignmentValueEscape => match self.consume_the_next_character() {
                 None => {
                     self.buffer('\\');
@@ -222,7 +363,7 @@ where
                     None => return self.unterminated_single_quote(),
                     Some('\0') => return self.err(ErrorKind::NullCharacter),
                     Some('\'') => return Ok(self.switch_to_return_state()),
-                    Some(c) => self.buffer(c),
+                    Some(c) => self.buffer_literal(c),
                 };
             },
             State::DoubleQuoted => loop {
@@ -239,7 +380,7 @@ where
                         self.return_states.push_back(self.state);
                         return Ok(self.switch_to(State::Dollar));
                     }
-                    Some(c) => self.buffer(c),
+                    Some(c) => self.buffer_literal(c),
                 };
             },
             State::DoubleQuotedEscape => match self.consume_the_next_character() {
@@ -247,27 +388,38 @@ where
                 Some('\0') => self.err(ErrorKind::NullCharacter),
                 Some('\n') => Ok(self.switch_to(State::DoubleQuoted)),
                 Some(c) if is_dq_escape(c) => {
-                    self.buffer(c);
+                    self.buffer_literal(c);
                     Ok(self.switch_to(State::DoubleQuoted))
                 }
                 Some(c) => {
-                    self.buffer('\\');
-                    self.buffer(c);
+                    self.buffer_literal('\\');
+                    self.buffer_literal(c);
                     Ok(self.switch_to(State::DoubleQuoted))
                 }
             },
             State::Dollar => match self.consume_the_next_character() {
                 Some('\0') => self.err(ErrorKind::NullCharacter),
+                // Templates keep what they cannot expand, like `$1` or `$(...)`, literally.
+                Some(c)
+                    if (is_shell_special_param(c) || c == '(')
+                        && self.return_states.back() == Some(&State::Template) =>
+                {
+                    self.buffer('$');
+                    Ok(self.reconsume_in_return_state())
+                }
                 Some(c) if is_shell_special_param(c) => {
                     self.err(ErrorKind::UnsupportedShellParameter(format!("${}", c)))
                 }
                 Some('(') => self.err(ErrorKind::UnsupportedCommandOrArithmeticExpansion),
                 Some('{') => {
-                    self.expansion_stack.push_back(self.cur_pos());
+                    self.quoted_expansion = self.in_quoted_pattern();
+                    self.expansion_stack
+                        .push_back((self.cur_pos(), ValueKind::Word));
                     self.flush_buffer(TokenKind::Characters);
                     Ok(self.switch_to(State::ComplexExpansionStart))
                 }
                 Some(c) if is_identifier_char(c) => {
+                    self.quoted_expansion = self.in_quoted_pattern();
                     self.flush_buffer(TokenKind::Characters);
                     self.buffer(c);
                     Ok(self.switch_to(State::SimpleExpansion))
@@ -287,6 +439,9 @@ where
             },
             State::ComplexExpansionStart => match self.consume_the_next_character() {
                 Some('\0') => self.err(ErrorKind::NullCharacter),
+                Some('#') if self.options.string_length => {
+                    Ok(self.switch_to(State::StringLengthStart))
+                }
                 Some(c) if is_shell_special_param(c) => {
                     self.err(ErrorKind::UnsupportedShellParameter(format!("${{{}}}", c)))
                 }
@@ -297,6 +452,30 @@ where
                 Some(c) => self.err(ErrorKind::InvalidCharacter(c)),
                 None => self.err_eof(),
             },
+            State::StringLengthStart => match self.consume_the_next_character() {
+                None => self.err_eof(),
+                Some('\0') => self.err(ErrorKind::NullCharacter),
+                Some('}') => self.err(ErrorKind::UnsupportedShellParameter("${#}".to_string())),
+                Some(c) if is_shell_special_param(c) => {
+                    self.err(ErrorKind::UnsupportedShellParameter(format!("${{#{}}}", c)))
+                }
+                Some(c) if is_identifier_start(c) => {
+                    self.buffer(c);
+                    Ok(self.switch_to(State::StringLength))
+                }
+                Some(c) => self.err(ErrorKind::InvalidCharacter(c)),
+            },
+            State::StringLength => match self.consume_the_next_character() {
+                None => self.unterminated_expansion(),
+                Some('\0') => self.err(ErrorKind::NullCharacter),
+                Some('}') => {
+                    self.expansion_stack.pop_back();
+                    self.flush_buffer(TokenKind::StringLength);
+                    Ok(self.switch_to_return_state())
+                }
+                Some(c) if is_identifier_char(c) => Ok(self.buffer(c)),
+                Some(c) => self.err(ErrorKind::InvalidCharacter(c)),
+            },
             State::ComplexExpansion => match self.consume_the_next_character() {
                 None => self.unterminated_expansion(),
                 Some('\0') => self.err(ErrorKind::NullCharacter),
@@ -316,6 +495,22 @@ where
                     self.emit(TokenKind::ExpansionOperator, c.to_string());
                     Ok(self.switch_to(State::ExpansionValue))
                 }
+                Some(c) if is_pattern_operator(c) && self.options.pattern_removal => {
+                    self.flush_buffer(TokenKind::StartExpansion);
+                    self.set_value_kind(ValueKind::Pattern);
+                    self.buffer(c);
+                    Ok(self.switch_to(State::PatternOperator))
+                }
+                Some('|') if self.options.filters => {
+                    self.flush_buffer(TokenKind::StartExpansion);
+                    Ok(self.switch_to(State::FilterName))
+                }
+                Some('/') if self.options.bash_expansions => {
+                    self.flush_buffer(TokenKind::StartExpansion);
+                    self.set_value_kind(ValueKind::ReplacementPattern);
+                    self.buffer('/');
+                    Ok(self.switch_to(State::ReplacementOperator))
+                }
                 Some(c) => self.err(ErrorKind::InvalidCharacter(c)),
             },
             State::ExpansionOperator => match self.consume_the_next_character() {
@@ -326,8 +521,99 @@ where
                     self.flush_buffer(TokenKind::ExpansionOperator);
                     Ok(self.switch_to(State::ExpansionValue))
                 }
+                Some(c) if self.options.bash_expansions && (is_blank(c) || c.is_ascii_digit()) => {
+                    self.flush_buffer(TokenKind::ExpansionOperator);
+                    Ok(self.reconsume_in(State::SubstringOffset))
+                }
+                Some(_) if self.options.resolvers => {
+                    self.flush_buffer(TokenKind::Resolve);
+                    Ok(self.reconsume_in(State::ExpansionValue))
+                }
+                Some(c) => self.err(ErrorKind::InvalidCharacter(c)),
+            },
+            State::PatternOperator => match self.consume_the_next_character() {
+                Some(c) if self.buf.starts_with(c) => {
+                    self.buffer(c);
+                    self.flush_buffer(TokenKind::ExpansionOperator);
+                    Ok(self.switch_to(State::ExpansionValue))
+                }
+                _ => {
+                    self.flush_buffer(TokenKind::ExpansionOperator);
+                    Ok(self.reconsume_in(State::ExpansionValue))
+                }
+            },
+            // The parser validates the integers, blanks included.
+            State::SubstringOffset | State::SubstringLength => {
+                match self.consume_the_next_character() {
+                    None => self.unterminated_expansion(),
+                    Some('\0') => self.err(ErrorKind::NullCharacter),
+                    Some(c) if is_blank(c) || c.is_ascii_digit() || c == '-' => Ok(self.buffer(c)),
+                    Some(':') if self.state == State::SubstringOffset => {
+                        self.flush_buffer(TokenKind::Characters);
+                        self.emit(TokenKind::ExpansionOperator, ":".to_string());
+                        Ok(self.switch_to(State::SubstringLength))
+                    }
+                    Some('}') => {
+                        self.expansion_stack.pop_back();
+                        self.flush_buffer(TokenKind::Characters);
+                        self.emit(TokenKind::EndExpansion, "}".to_string());
+                        Ok(self.switch_to_return_state())
+                    }
+                    Some(c) => self.err(ErrorKind::InvalidCharacter(c)),
+                }
+            }
+            State::ReplacementOperator => match self.consume_the_next_character() {
+                Some(c @ ('/' | '#' | '%')) => {
+                    self.buffer(c);
+                    self.flush_buffer(TokenKind::ExpansionOperator);
+                    Ok(self.switch_to(State::ExpansionValue))
+                }
+                _ => {
+                    self.flush_buffer(TokenKind::ExpansionOperator);
+                    Ok(self.reconsume_in(State::ExpansionValue))
+                }
+            },
+            State::ReplacementSeparator => {
+                self.consume_the_next_character();
+                self.emit(TokenKind::ExpansionOperator, "/".to_string());
+                self.set_value_kind(ValueKind::Word);
+                Ok(self.switch_to(State::ExpansionValue))
+            }
+            State::FilterName => match self.consume_the_next_character() {
+                None => self.unterminated_expansion(),
+                Some('\0') => self.err(ErrorKind::NullCharacter),
+                Some(c) if is_identifier_start(c) => Ok(self.buffer(c)),
+                Some(c) if is_identifier_char(c) && !self.buf.is_empty() => Ok(self.buffer(c)),
+                Some(':') if !self.buf.is_empty() => {
+                    self.buffer(':');
+                    Ok(self.switch_to(State::FilterArgument))
+                }
+                Some('|') if !self.buf.is_empty() => Ok(self.flush_buffer(TokenKind::Filter)),
+                Some('}') if !self.buf.is_empty() => Ok(self.end_filters()),
                 Some(c) => self.err(ErrorKind::InvalidCharacter(c)),
             },
+            State::FilterArgument => match self.consume_the_next_character() {
+                None => self.unterminated_expansion(),
+                Some('\0') => self.err(ErrorKind::NullCharacter),
+                Some('|') => {
+                    self.flush_buffer(TokenKind::Filter);
+                    Ok(self.switch_to(State::FilterName))
+                }
+                Some('}') => Ok(self.end_filters()),
+                Some(c) => Ok(self.buffer(c)),
+            },
+            State::TildePrefix => match self.consume_the_next_character() {
+                Some('~') if self.buf.is_empty() => Ok(self.buffer('~')),
+                Some(c) if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') => {
+                    Ok(self.buffer(c))
+                }
+                None | Some('/' | ':' | ' ' | '\t' | '\n') => {
+                    self.flush_buffer(TokenKind::Tilde);
+                    Ok(self.reconsume_in(State::AssignmentValue))
+                }
+                // Not a tilde-prefix, the buffered characters are kept literally.
+                Some(_) => Ok(self.reconsume_in(State::AssignmentValue)),
+            },
             State::ExpansionValue => match self.consume_the_next_character() {
                 None => self.unterminated_expansion(),
                 Some('\0') => self.err(ErrorKind::NullCharacter),
@@ -338,6 +624,10 @@ where
                     self.emit(TokenKind::EndExpansion, "}".to_string());
                     Ok(self.switch_to_return_state())
                 }
+                Some('/') if self.value_kind() == Some(ValueKind::ReplacementPattern) => {
+                    self.flush_buffer(TokenKind::Characters);
+                    Ok(self.reconsume_in(State::ReplacementSeparator))
+                }
                 Some('\\') => Ok(self.switch_to(State::ExpansionValueEscape)),
                 Some('$') => {
                     self.return_states.push_back(self.state);
@@ -365,14 +655,14 @@ where
                 Some('\0') => self.err(ErrorKind::NullCharacter),
                 Some('\n') => Ok(self.switch_to(State::ExpansionValue)),
                 Some(c) if is_dq_escape(c) => {
-                    self.buffer(c);
+                    self.buffer_literal(c);
                     Ok(self.switch_to(State::ExpansionValue))
                 }
                 Some(c) => {
                     if !self.quoting_stack.is_empty() {
-                        self.buffer('\\');
+                        self.buffer_literal('\\');
                     }
-                    self.buffer(c);
+                    self.buffer_literal(c);
                     Ok(self.switch_to(State::ExpansionValue))
                 }
             },
@@ -400,15 +690,26 @@ where
     fn consume_the_next_character(&mut self) -> Option<char> {
         if self.reconsume {
             self.reconsume = false;
+            let entry = self.trace.as_mut().and_then(|trace| trace.last_mut());
+            if let (Some(entry), Some(_)) = (entry, self.cc) {
+                entry.state = self.state;
+            }
         } else {
-            self.cc = self.input.next().map(|c| {
+            self.cc = self.input.next().inspect(|&c| {
+                if let Some(trace) = self.trace.as_mut() {
+                    trace.push(TraceEntry {
+                        offset: self.offset,
+                        ch: c,
+                        state: self.state,
+                    });
+                }
+                self.offset += c.len_utf8();
                 if c == '\n' {
                     self.line += 1;
                     self.column = 0;
                 } else {
                     self.column += 1;
                 }
-                c
             });
         }
         self.cc
@@ -428,8 +729,14 @@ where
 
     fn flush_buffer(&mut self, kind: TokenKind) {
         if !self.buf.is_empty() {
-            self.queue
-                .push_back(Token::new(kind, self.buf.clone(), self.buf_pos));
+            let mut token = Token::new(kind, self.buf.clone(), self.buf_pos);
+            if matches!(
+                token.kind,
+                TokenKind::SimpleExpansion | TokenKind::StartExpansion | TokenKind::StringLength
+            ) {
+                token.quoted = std::mem::take(&mut self.quoted_expansion);
+            }
+            self.queue.push_back(token);
             self.buf.clear();
         }
     }
@@ -441,6 +748,45 @@ where
         self.buf.push(c);
     }
 
+    /// Buffers a quoted or escaped character,
+    /// escaping it if it would otherwise be special in a pattern.
+    fn buffer_literal(&mut self, c: char) {
+        if self.in_pattern() && is_pattern_special_char(c) {
+            self.buffer('\\');
+        }
+        self.buffer(c);
+    }
+
+    fn in_pattern(&self) -> bool {
+        matches!(
+            self.value_kind(),
+            Some(ValueKind::Pattern | ValueKind::ReplacementPattern)
+        )
+    }
+
+    /// Returns whether an expansion starting at the current `$` is double-quoted inside a pattern.
+    fn in_quoted_pattern(&self) -> bool {
+        self.in_pattern() && self.return_states.back() == Some(&State::DoubleQuoted)
+    }
+
+    fn end_filters(&mut self) {
+        self.expansion_stack.pop_back();
+        self.flush_buffer(TokenKind::Filter);
+        self.emit(TokenKind::EndExpansion, "}".to_string());
+        self.switch_to_return_state();
+    }
+
+    /// Returns how the value of the innermost open expansion is tokenized.
+    fn value_kind(&self) -> Option<ValueKind> {
+        self.expansion_stack.back().map(|(_, kind)| *kind)
+    }
+
+    fn set_value_kind(&mut self, kind: ValueKind) {
+        if let Some((_, value_kind)) = self.expansion_stack.back_mut() {
+            *value_kind = kind;
+        }
+    }
+
     fn cur_pos(&self) -> Position {
         Position::new(self.line, self.column)
     }
@@ -474,7 +820,7 @@ where
     }
 
     fn unterminated_expansion(&mut self) -> Result<(), SyntaxError> {
-        let pos = self.expansion_stack.pop_back().unwrap();
+        let (pos, _) = self.expansion_stack.pop_back().unwrap();
         self.err_at(ErrorKind::UnterminatedExpansion, pos)
     }
 }
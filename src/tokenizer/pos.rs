@@ -1,3 +1,4 @@
+/// A 1-based line and column in a dotenv file, counted in characters.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Position {
     pub line: usize,
@@ -8,4 +9,33 @@ impl Position {
     pub fn new(line: usize, column: usize) -> Self {
         Self { line, column }
     }
+
+    /// Returns the position of the character at the given byte offset of the input.
+    pub(crate) fn at_offset(input: &str, offset: usize) -> Self {
+        let before = &input[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before
+            .rsplit('\n')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .count()
+            + 1;
+        Self::new(line, column)
+    }
+
+    /// Returns the byte offset of the character at this position of the input.
+    pub(crate) fn offset(&self, input: &str) -> usize {
+        let line_start = match self.line {
+            0 | 1 => 0,
+            line => input
+                .match_indices('\n')
+                .nth(line - 2)
+                .map_or(input.len(), |(i, _)| i + 1),
+        };
+        input[line_start..]
+            .char_indices()
+            .nth(self.column.saturating_sub(1))
+            .map_or(input.len(), |(i, _)| line_start + i)
+    }
 }
@@ -58,6 +58,244 @@ fn simple_raw_values() -> Result<(), SyntaxError> {
     assert_tokens(input, expected)
 }
 
+#[test]
+fn export_prefix() -> Result<(), SyntaxError> {
+    let input = "export A=1\nexport\t B=2 export=3";
+    let options = Options {
+        export_prefix: true,
+        ..Default::default()
+    };
+    let tokens: Vec<_> =
+        Tokenizer::with_options(input.chars(), None, options).collect::<Result<_, _>>()?;
+    let expected = vec![
+        tok!(Export, "export", 1, 1),
+        tok!(Assign, "A", 1, 8),
+        tok!(Characters, "1", 1, 10),
+        tok!(Export, "export", 2, 1),
+        tok!(Assign, "B", 2, 9),
+        tok!(Characters, "2", 2, 11),
+        tok!(Assign, "export", 2, 13),
+        tok!(Characters, "3", 2, 20),
+        tok!(Eof, "", 2, 21),
+    ];
+    assert_eq!(expected, tokens);
+    Ok(())
+}
+
+#[rstest]
+#[case::disabled(false, "export A=1", ErrorKind::InvalidCharacter(' '))]
+#[case::newline(true, "export\nA=1", ErrorKind::InvalidCharacter('\n'))]
+#[case::eof(true, "export ", ErrorKind::Eof)]
+#[case::no_name(true, "export =1", ErrorKind::InvalidCharacter('='))]
+fn export_prefix_errors(
+    #[case] export_prefix: bool,
+    #[case] input: &str,
+    #[case] expected: ErrorKind,
+) {
+    let options = Options {
+        export_prefix,
+        ..Default::default()
+    };
+    let err = Tokenizer::with_options(input.chars(), None, options)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap_err();
+    assert_eq!(expected, err.kind());
+}
+
+#[test]
+fn string_length() -> Result<(), SyntaxError> {
+    let input = "A=${#B}-${C:-${#D_1}}";
+    let options = Options {
+        string_length: true,
+        ..Default::default()
+    };
+    let tokens: Vec<_> =
+        Tokenizer::with_options(input.chars(), None, options).collect::<Result<_, _>>()?;
+    let expected = vec![
+        tok!(Assign, "A", 1, 1),
+        tok!(StringLength, "B", 1, 6),
+        tok!(Characters, "-", 1, 8),
+        tok!(StartExpansion, "C", 1, 11),
+        tok!(ExpansionOperator, ":-", 1, 12),
+        tok!(StringLength, "D_1", 1, 17),
+        tok!(EndExpansion, "}", 1, 21),
+        tok!(Eof, "", 1, 22),
+    ];
+    assert_eq!(expected, tokens);
+    Ok(())
+}
+
+#[rstest]
+#[case::disabled(false, "A=${#B}", ErrorKind::UnsupportedShellParameter("${#}".into()))]
+#[case::parameter_count(true, "A=${#}", ErrorKind::UnsupportedShellParameter("${#}".into()))]
+#[case::special_parameter(true, "A=${#@}", ErrorKind::UnsupportedShellParameter("${#@}".into()))]
+#[case::operator(true, "A=${#B:-1}", ErrorKind::InvalidCharacter(':'))]
+#[case::unterminated(true, "A=${#B", ErrorKind::UnterminatedExpansion)]
+fn string_length_errors(
+    #[case] string_length: bool,
+    #[case] input: &str,
+    #[case] expected: ErrorKind,
+) {
+    let options = Options {
+        string_length,
+        ..Default::default()
+    };
+    let err = Tokenizer::with_options(input.chars(), None, options)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap_err();
+    assert_eq!(expected, err.kind());
+}
+
+#[rstest]
+#[case::smallest_prefix("A=${B#*/}", vec![tok!(StartExpansion, "B", 1, 5), tok!(ExpansionOperator, "#", 1, 6), tok!(Characters, "*/", 1, 7)])]
+#[case::largest_suffix("A=${B%%.*}", vec![tok!(StartExpansion, "B", 1, 5), tok!(ExpansionOperator, "%%", 1, 6), tok!(Characters, ".*", 1, 8)])]
+#[case::empty_pattern("A=${B%}", vec![tok!(StartExpansion, "B", 1, 5), tok!(ExpansionOperator, "%", 1, 6)])]
+#[case::quoted_pattern(r#"A=${B#'*'"?"\[}"#, vec![tok!(StartExpansion, "B", 1, 5), tok!(ExpansionOperator, "#", 1, 6), tok!(Characters, r"\*\?\[", 1, 8)])]
+#[case::nested_default("A=${B#${C:-'*'}}", vec![tok!(StartExpansion, "B", 1, 5), tok!(ExpansionOperator, "#", 1, 6), tok!(StartExpansion, "C", 1, 9), tok!(ExpansionOperator, ":-", 1, 10), tok!(Characters, "*", 1, 13), tok!(EndExpansion, "}", 1, 15)])]
+fn pattern_removal(#[case] input: &str, #[case] expected: Vec<Token>) -> Result<(), SyntaxError> {
+    let options = Options {
+        pattern_removal: true,
+        ..Default::default()
+    };
+    let tokens: Vec<_> =
+        Tokenizer::with_options(input.chars(), None, options).collect::<Result<_, _>>()?;
+    assert_eq!(tok!(Assign, "A", 1, 1), tokens[0]);
+    assert_eq!(expected, tokens[1..tokens.len() - 2]);
+    Ok(())
+}
+
+#[test]
+fn pattern_removal_disabled() {
+    let err = tokenize("A=${B%/}").unwrap_err();
+    assert_eq!(ErrorKind::InvalidCharacter('%'), err.kind());
+}
+
+#[rstest]
+#[case::substring("A=${B:1:2}", vec![tok!(StartExpansion, "B", 1, 5), tok!(ExpansionOperator, ":", 1, 6), tok!(Characters, "1", 1, 7), tok!(ExpansionOperator, ":", 1, 8), tok!(Characters, "2", 1, 9)])]
+#[case::negative_offset("A=${B: -3}", vec![tok!(StartExpansion, "B", 1, 5), tok!(ExpansionOperator, ":", 1, 6), tok!(Characters, " -3", 1, 7)])]
+#[case::empty_length("A=${B:2:}", vec![tok!(StartExpansion, "B", 1, 5), tok!(ExpansionOperator, ":", 1, 6), tok!(Characters, "2", 1, 7), tok!(ExpansionOperator, ":", 1, 8)])]
+#[case::replace_first("A=${B/a/b}", vec![tok!(StartExpansion, "B", 1, 5), tok!(ExpansionOperator, "/", 1, 6), tok!(Characters, "a", 1, 7), tok!(ExpansionOperator, "/", 1, 8), tok!(Characters, "b", 1, 9)])]
+#[case::replace_all("A=${B//:}", vec![tok!(StartExpansion, "B", 1, 5), tok!(ExpansionOperator, "//", 1, 6), tok!(Characters, ":", 1, 8)])]
+#[case::replace_prefix("A=${B/#x/}", vec![tok!(StartExpansion, "B", 1, 5), tok!(ExpansionOperator, "/#", 1, 6), tok!(Characters, "x", 1, 8), tok!(ExpansionOperator, "/", 1, 9)])]
+#[case::quoted_pattern("A=${B/'*'/*}", vec![tok!(StartExpansion, "B", 1, 5), tok!(ExpansionOperator, "/", 1, 6), tok!(Characters, r"\*", 1, 8), tok!(ExpansionOperator, "/", 1, 10), tok!(Characters, "*", 1, 11)])]
+#[case::escaped_slash(r"A=${B/\//-}", vec![tok!(StartExpansion, "B", 1, 5), tok!(ExpansionOperator, "/", 1, 6), tok!(Characters, "/", 1, 8), tok!(ExpansionOperator, "/", 1, 9), tok!(Characters, "-", 1, 10)])]
+#[case::nested("A=${B/${C/x/y}/z}", vec![tok!(StartExpansion, "B", 1, 5), tok!(ExpansionOperator, "/", 1, 6), tok!(StartExpansion, "C", 1, 9), tok!(ExpansionOperator, "/", 1, 10), tok!(Characters, "x", 1, 11), tok!(ExpansionOperator, "/", 1, 12), tok!(Characters, "y", 1, 13), tok!(EndExpansion, "}", 1, 14), tok!(ExpansionOperator, "/", 1, 15), tok!(Characters, "z", 1, 16)])]
+fn bash_expansions(#[case] input: &str, #[case] expected: Vec<Token>) -> Result<(), SyntaxError> {
+    let options = Options {
+        bash_expansions: true,
+        ..Default::default()
+    };
+    let tokens: Vec<_> =
+        Tokenizer::with_options(input.chars(), None, options).collect::<Result<_, _>>()?;
+    assert_eq!(tok!(Assign, "A", 1, 1), tokens[0]);
+    assert_eq!(expected, tokens[1..tokens.len() - 2]);
+    Ok(())
+}
+
+#[rstest]
+#[case::substring_disabled(false, "A=${B:1}", ErrorKind::InvalidCharacter('1'))]
+#[case::replacement_disabled(false, "A=${B/x/y}", ErrorKind::InvalidCharacter('/'))]
+#[case::invalid_offset(true, "A=${B:1x}", ErrorKind::InvalidCharacter('x'))]
+#[case::extra_colon(true, "A=${B:1:2:3}", ErrorKind::InvalidCharacter(':'))]
+#[case::unterminated_substring(true, "A=${B:1", ErrorKind::UnterminatedExpansion)]
+#[case::unterminated_replacement(true, "A=${B/x/y", ErrorKind::UnterminatedExpansion)]
+fn bash_expansions_errors(
+    #[case] bash_expansions: bool,
+    #[case] input: &str,
+    #[case] expected: ErrorKind,
+) {
+    let options = Options {
+        bash_expansions,
+        ..Default::default()
+    };
+    let err = Tokenizer::with_options(input.chars(), None, options)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap_err();
+    assert_eq!(expected, err.kind());
+}
+
+#[rstest]
+#[case::single("A=${B|upper}", vec![tok!(StartExpansion, "B", 1, 5), tok!(Filter, "upper", 1, 7)])]
+#[case::chain("A=${B|split:,|first}", vec![tok!(StartExpansion, "B", 1, 5), tok!(Filter, "split:,", 1, 7), tok!(Filter, "first", 1, 15)])]
+#[case::empty_argument("A=${B|join:}", vec![tok!(StartExpansion, "B", 1, 5), tok!(Filter, "join:", 1, 7)])]
+fn filters(#[case] input: &str, #[case] expected: Vec<Token>) -> Result<(), SyntaxError> {
+    let options = Options {
+        filters: true,
+        ..Default::default()
+    };
+    let tokens: Vec<_> =
+        Tokenizer::with_options(input.chars(), None, options).collect::<Result<_, _>>()?;
+    assert_eq!(tok!(Assign, "A", 1, 1), tokens[0]);
+    assert_eq!(expected, tokens[1..tokens.len() - 2]);
+    Ok(())
+}
+
+#[rstest]
+#[case::disabled(false, "A=${B|upper}", ErrorKind::InvalidCharacter('|'))]
+#[case::empty_name(true, "A=${B|}", ErrorKind::InvalidCharacter('}'))]
+#[case::empty_chain(true, "A=${B|upper||lower}", ErrorKind::InvalidCharacter('|'))]
+#[case::invalid_name(true, "A=${B|1st}", ErrorKind::InvalidCharacter('1'))]
+#[case::unterminated(true, "A=${B|split:,", ErrorKind::UnterminatedExpansion)]
+fn filters_errors(#[case] filters: bool, #[case] input: &str, #[case] expected: ErrorKind) {
+    let options = Options {
+        filters,
+        ..Default::default()
+    };
+    let err = Tokenizer::with_options(input.chars(), None, options)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap_err();
+    assert_eq!(expected, err.kind());
+}
+
+#[rstest]
+#[case::path("A=${file:/run/secrets/db}", vec![tok!(StartExpansion, "file", 1, 5), tok!(Resolve, ":", 1, 9), tok!(Characters, "/run/secrets/db", 1, 10)])]
+#[case::nested("A=${file:$DIR/db}", vec![tok!(StartExpansion, "file", 1, 5), tok!(Resolve, ":", 1, 9), tok!(SimpleExpansion, "DIR", 1, 11), tok!(Characters, "/db", 1, 14)])]
+#[case::empty("A=${now:}", vec![tok!(StartExpansion, "now", 1, 5), tok!(Resolve, ":", 1, 8)])]
+#[case::operator("A=${B:-x}", vec![tok!(StartExpansion, "B", 1, 5), tok!(ExpansionOperator, ":-", 1, 6), tok!(Characters, "x", 1, 8)])]
+fn resolvers(#[case] input: &str, #[case] expected: Vec<Token>) -> Result<(), SyntaxError> {
+    let options = Options {
+        resolvers: true,
+        ..Default::default()
+    };
+    let tokens: Vec<_> =
+        Tokenizer::with_options(input.chars(), None, options).collect::<Result<_, _>>()?;
+    assert_eq!(tok!(Assign, "A", 1, 1), tokens[0]);
+    assert_eq!(expected, tokens[1..tokens.len() - 2]);
+    Ok(())
+}
+
+#[test]
+fn resolvers_disabled() {
+    let err = tokenize("A=${file:/run/secrets/db}").unwrap_err();
+    assert_eq!(ErrorKind::InvalidCharacter('/'), err.kind());
+}
+
+#[rstest]
+#[case::home(true, "A=~/bin", vec![tok!(Tilde, "~", 1, 3), tok!(Characters, "/bin", 1, 4)])]
+#[case::alone(true, "A=~", vec![tok!(Tilde, "~", 1, 3)])]
+#[case::after_colons(true, "A=~user/x:~:~b", vec![tok!(Tilde, "~user", 1, 3), tok!(Characters, "/x:", 1, 8), tok!(Tilde, "~", 1, 11), tok!(Characters, ":", 1, 12), tok!(Tilde, "~b", 1, 13)])]
+#[case::quoted(true, "A='~'/x", vec![tok!(Characters, "~/x", 1, 4)])]
+#[case::escaped(true, r"A=\~", vec![tok!(Characters, "~", 1, 4)])]
+#[case::quoted_user(true, "A=~'x'", vec![tok!(Characters, "~x", 1, 3)])]
+#[case::inside_word(true, "A=a~", vec![tok!(Characters, "a~", 1, 3)])]
+#[case::after_expansion(true, "A=$B~", vec![tok!(SimpleExpansion, "B", 1, 4), tok!(Characters, "~", 1, 5)])]
+#[case::disabled(false, "A=~/x", vec![tok!(Characters, "~/x", 1, 3)])]
+fn tilde_prefix(
+    #[case] tilde_expansion: bool,
+    #[case] input: &str,
+    #[case] expected: Vec<Token>,
+) -> Result<(), SyntaxError> {
+    let options = Options {
+        tilde_expansion,
+        ..Default::default()
+    };
+    let tokens: Vec<_> =
+        Tokenizer::with_options(input.chars(), None, options).collect::<Result<_, _>>()?;
+    assert_eq!(tok!(Assign, "A", 1, 1), tokens[0]);
+    assert_eq!(expected, tokens[1..tokens.len() - 1]);
+    Ok(())
+}
+
 #[rstest]
 #[case("&", 1, 1)]
 #[case("abc", 1, 4)]
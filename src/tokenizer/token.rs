@@ -4,10 +4,15 @@ use super::pos::Position;
 pub enum TokenKind {
     Eof,
     Characters,
+    Export,
     Assign,
     SimpleExpansion,
     StartExpansion,
+    StringLength,
+    Tilde,
     ExpansionOperator,
+    Filter,
+    Resolve,
     EndExpansion,
 }
 
@@ -16,6 +21,8 @@ pub struct Token {
     pub kind: TokenKind,
     pub value: String,
     pub position: Position,
+    /// Whether an expansion is double-quoted inside a pattern, so that its value matches literally.
+    pub quoted: bool,
 }
 
 impl Token {
@@ -24,6 +31,7 @@ impl Token {
             kind,
             value,
             position,
+            quoted: false,
         }
     }
 }
@@ -0,0 +1,288 @@
+//! Evaluated variables with typed accessors, reporting where invalid values were defined.
+//!
+//! ```
+//! use potenv::vars::{Env, Provenance};
+//! use std::time::Duration;
+//!
+//! let env = Env::from_iter([
+//!     ("PORT".to_owned(), "8080".to_owned(), Provenance::Environment),
+//!     ("DEBUG".to_owned(), "yes".to_owned(), Provenance::Environment),
+//!     ("TIMEOUT".to_owned(), "1m30s".to_owned(), Provenance::Environment),
+//!     ("HOSTS".to_owned(), "a.example, b.example".to_owned(), Provenance::Environment),
+//! ]);
+//! assert_eq!(8080, env.get::<u16>("PORT").unwrap());
+//! assert!(env.get_bool("DEBUG").unwrap());
+//! assert_eq!(Duration::from_secs(90), env.get_duration("TIMEOUT").unwrap());
+//! assert_eq!(vec!["a.example", "b.example"], env.get_list::<String>("HOSTS", ",").unwrap());
+//! ```
+
+use std::{
+    fmt::{self, Display},
+    net::{SocketAddr, ToSocketAddrs},
+    path::PathBuf,
+    str::FromStr,
+    time::Duration,
+};
+
+use indexmap::IndexMap;
+use thiserror::Error;
+
+use crate::{
+    evaluator::Scope,
+    secrets::{FileSecrets, SecretFile, SecretFileError},
+    tokenizer::pos::Position,
+};
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Error)]
+pub enum VarError {
+    #[error("Undefined variable ${0}")]
+    Undefined(String),
+    #[error("Invalid value for variable ${name} {provenance}: {message}")]
+    Invalid {
+        name: String,
+        provenance: Box<Provenance>,
+        message: String,
+    },
+}
+
+/// Where the value of a variable comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Provenance {
+    /// An assignment, or a `${NAME:=word}` expansion, in a dotenv file.
+    File {
+        /// The dotenv file, if known.
+        file: Option<PathBuf>,
+        /// The position of the variable name.
+        position: Position,
+    },
+    /// The environment, which took precedence over the dotenv files.
+    Environment,
+    /// A file referenced by a `_FILE` variable.
+    SecretFile(SecretFile),
+}
+
+impl Display for Provenance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            // Imported formats without line information, like JSON, leave the position unset.
+            Self::File { file, position } => match (file, position.line) {
+                (Some(file), 0) => write!(f, "defined in {}", file.display()),
+                (Some(file), line) => {
+                    let column = position.column;
+                    write!(f, "defined at {}:{line}:{column}", file.display())
+                }
+                (None, 0) => f.write_str("defined in a dotenv file"),
+                (None, line) => write!(f, "defined at {line}:{}", position.column),
+            },
+            Self::Environment => f.write_str("from the environment"),
+            Self::SecretFile(secret) => {
+                write!(f, "read from {:?} (${})", secret.path, secret.variable)
+            }
+        }
+    }
+}
+
+/// The value of a variable and its provenance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Var {
+    pub value: String,
+    pub provenance: Provenance,
+}
+
+/// Evaluated variables, in declaration order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Env {
+    vars: IndexMap<String, Var>,
+}
+
+impl Env {
+    pub fn len(&self) -> usize {
+        self.vars.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vars.is_empty()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.vars.contains_key(name)
+    }
+
+    pub fn var(&self, name: &str) -> Option<&Var> {
+        self.vars.get(name)
+    }
+
+    /// Returns the raw value of a variable.
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        self.vars.get(name).map(|var| var.value.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Var)> {
+        self.vars.iter().map(|(name, var)| (name.as_str(), var))
+    }
+
+    /// Parses the value of a variable with its [FromStr] implementation,
+    /// e.g. `env.get::<u16>("PORT")`.
+    pub fn get<T>(&self, name: &str) -> Result<T, VarError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.parse(name, |value| {
+            value.parse().map_err(|err: T::Err| err.to_string())
+        })
+    }
+
+    /// Parses a boolean, accepting `1`, `true`, `yes`, `y` and `on` for true,
+    /// and `0`, `false`, `no`, `n`, `off` and the empty string for false, ignoring case.
+    pub fn get_bool(&self, name: &str) -> Result<bool, VarError> {
+        self.parse(name, |value| match value.to_ascii_lowercase().as_str() {
+            "1" | "true" | "yes" | "y" | "on" => Ok(true),
+            "0" | "false" | "no" | "n" | "off" | "" => Ok(false),
+            _ => Err("expected a boolean, like true or false".to_owned()),
+        })
+    }
+
+    /// Parses a list of items separated by `separator`, ignoring blanks around items.
+    /// An empty value is an empty list.
+    pub fn get_list<T>(&self, name: &str, separator: &str) -> Result<Vec<T>, VarError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.parse(name, |value| {
+            if value.trim().is_empty() {
+                return Ok(Vec::new());
+            }
+            value
+                .split(separator)
+                .map(str::trim)
+                .map(|item| {
+                    item.parse()
+                        .map_err(|err: T::Err| format!("invalid item {:?}: {}", item, err))
+                })
+                .collect()
+        })
+    }
+
+    /// Parses a duration made of integers followed by a unit, like `1h30m` or `250ms`.
+    /// The units are `d`, `h`, `m`, `s`, `ms`, `us` and `ns`, and a number alone is in seconds.
+    pub fn get_duration(&self, name: &str) -> Result<Duration, VarError> {
+        self.parse(name, parse_duration)
+    }
+
+    /// Parses a socket address, resolving host names like `localhost:8080`
+    /// to their first address.
+    pub fn get_socket_addr(&self, name: &str) -> Result<SocketAddr, VarError> {
+        self.parse(name, |value| {
+            if let Ok(addr) = value.parse() {
+                return Ok(addr);
+            }
+            value
+                .to_socket_addrs()
+                .map_err(|err| err.to_string())?
+                .next()
+                .ok_or_else(|| "the host name has no address".to_owned())
+        })
+    }
+
+    /// Parses an absolute URL, like `postgres://db:5432/app`.
+    /// Requires the `url` feature.
+    #[cfg(feature = "url")]
+    pub fn get_url(&self, name: &str) -> Result<url::Url, VarError> {
+        self.get(name)
+    }
+
+    /// Defines the variables of the `_FILE` variables, as described in [FileSecrets::apply].
+    pub(crate) fn apply_secrets(
+        &mut self,
+        file_secrets: &FileSecrets,
+    ) -> Result<(), SecretFileError> {
+        let mut scope: Scope = self
+            .iter()
+            .map(|(name, var)| (name.to_owned(), var.value.clone()))
+            .collect();
+        for secret in file_secrets.apply(&mut scope)? {
+            let value = scope.swap_remove(&secret.name).unwrap_or_default();
+            let name = secret.name.clone();
+            let provenance = Provenance::SecretFile(secret);
+            self.vars.insert(name, Var { value, provenance });
+        }
+        Ok(())
+    }
+
+    fn parse<T, F>(&self, name: &str, f: F) -> Result<T, VarError>
+    where
+        F: FnOnce(&str) -> Result<T, String>,
+    {
+        let var = self
+            .var(name)
+            .ok_or_else(|| VarError::Undefined(name.to_owned()))?;
+        f(&var.value).map_err(|message| VarError::Invalid {
+            name: name.to_owned(),
+            provenance: Box::new(var.provenance.clone()),
+            message,
+        })
+    }
+}
+
+impl FromIterator<(String, String, Provenance)> for Env {
+    fn from_iter<I: IntoIterator<Item = (String, String, Provenance)>>(iter: I) -> Self {
+        let vars = iter
+            .into_iter()
+            .map(|(name, value, provenance)| (name, Var { value, provenance }))
+            .collect();
+        Self { vars }
+    }
+}
+
+impl From<Env> for Scope {
+    fn from(env: Env) -> Self {
+        env.vars
+            .into_iter()
+            .map(|(name, var)| (name, var.value))
+            .collect()
+    }
+}
+
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let invalid = || {
+        format!(
+            "invalid duration {:?}, expected e.g. 30s, 5m or 1h30m",
+            value
+        )
+    };
+    if let Ok(secs) = value.parse() {
+        return Ok(Duration::from_secs(secs));
+    }
+    let mut rest = value;
+    let mut duration = Duration::ZERO;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let unit_len = rest[digits..]
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len() - digits);
+        let amount: u64 = rest[..digits].parse().map_err(|_| invalid())?;
+        let part = match &rest[digits..digits + unit_len] {
+            "d" => Duration::from_secs(amount.saturating_mul(86400)),
+            "h" => Duration::from_secs(amount.saturating_mul(3600)),
+            "m" => Duration::from_secs(amount.saturating_mul(60)),
+            "s" => Duration::from_secs(amount),
+            "ms" => Duration::from_millis(amount),
+            "us" | "µs" => Duration::from_micros(amount),
+            "ns" => Duration::from_nanos(amount),
+            _ => return Err(invalid()),
+        };
+        duration = duration.saturating_add(part);
+        rest = &rest[digits + unit_len..];
+    }
+    match value.is_empty() {
+        true => Err(invalid()),
+        false => Ok(duration),
+    }
+}
@@ -0,0 +1,162 @@
+use std::{net::SocketAddr, path::PathBuf, time::Duration};
+
+use rstest::rstest;
+
+use super::{Env, Provenance, VarError};
+use crate::{secrets::SecretFile, tokenizer::pos::Position};
+
+fn env(value: &str) -> Env {
+    let provenance = Provenance::File {
+        file: Some(PathBuf::from(".env")),
+        position: Position::new(3, 1),
+    };
+    Env::from_iter([("X".to_owned(), value.to_owned(), provenance)])
+}
+
+fn message(err: VarError) -> String {
+    match err {
+        VarError::Invalid { message, .. } => message,
+        err => panic!("unexpected error {:?}", err),
+    }
+}
+
+#[test]
+fn test_get() {
+    assert_eq!(8080, env("8080").get::<u16>("X").unwrap());
+    assert_eq!(-1.5, env("-1.5").get::<f64>("X").unwrap());
+    let err = env("80000").get::<u16>("X").unwrap_err();
+    assert_eq!(
+        "Invalid value for variable $X defined at .env:3:1: number too large to fit in target type",
+        err.to_string()
+    );
+    let err = env("1").get::<u16>("Y").unwrap_err();
+    assert!(matches!(err, VarError::Undefined(name) if name == "Y"));
+}
+
+#[rstest]
+#[case::one("1", true)]
+#[case::true_("true", true)]
+#[case::upper("TRUE", true)]
+#[case::yes("Yes", true)]
+#[case::y("y", true)]
+#[case::on("on", true)]
+#[case::zero("0", false)]
+#[case::false_("False", false)]
+#[case::no("no", false)]
+#[case::n("N", false)]
+#[case::off("off", false)]
+#[case::empty("", false)]
+fn test_get_bool(#[case] value: &str, #[case] expected: bool) {
+    assert_eq!(expected, env(value).get_bool("X").unwrap());
+}
+
+#[rstest]
+#[case::number("2")]
+#[case::word("enabled")]
+#[case::blank(" ")]
+fn test_invalid_bool(#[case] value: &str) {
+    assert!(env(value).get_bool("X").is_err());
+}
+
+#[rstest]
+#[case::empty("", ",", vec![])]
+#[case::blank("  ", ",", vec![])]
+#[case::single("1", ",", vec![1])]
+#[case::trimmed(" 1, 2 ,3 ", ",", vec![1, 2, 3])]
+#[case::separator("1::2", "::", vec![1, 2])]
+fn test_get_list(#[case] value: &str, #[case] separator: &str, #[case] expected: Vec<u8>) {
+    assert_eq!(expected, env(value).get_list::<u8>("X", separator).unwrap());
+}
+
+#[test]
+fn test_invalid_list() {
+    let err = env("1,,2").get_list::<u8>("X", ",").unwrap_err();
+    assert_eq!(
+        "invalid item \"\": cannot parse integer from empty string",
+        message(err)
+    );
+}
+
+#[rstest]
+#[case::seconds("30", Duration::from_secs(30))]
+#[case::seconds_unit("30s", Duration::from_secs(30))]
+#[case::minutes("5m", Duration::from_secs(300))]
+#[case::compound("1h30m", Duration::from_secs(5400))]
+#[case::days("2d", Duration::from_secs(172800))]
+#[case::millis("250ms", Duration::from_millis(250))]
+#[case::micros("10us", Duration::from_micros(10))]
+#[case::micro_sign("10µs", Duration::from_micros(10))]
+#[case::nanos("1s5ns", Duration::new(1, 5))]
+fn test_get_duration(#[case] value: &str, #[case] expected: Duration) {
+    assert_eq!(expected, env(value).get_duration("X").unwrap());
+}
+
+#[rstest]
+#[case::empty("")]
+#[case::unit_only("s")]
+#[case::unknown_unit("5w")]
+#[case::missing_unit("1h30")]
+#[case::blank("5 s")]
+#[case::negative("-5s")]
+#[case::decimal("1.5h")]
+fn test_invalid_duration(#[case] value: &str) {
+    assert!(env(value).get_duration("X").is_err());
+}
+
+#[test]
+fn test_get_socket_addr() {
+    let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+    assert_eq!(addr, env("127.0.0.1:8080").get_socket_addr("X").unwrap());
+    let addr: SocketAddr = "[::1]:443".parse().unwrap();
+    assert_eq!(addr, env("[::1]:443").get_socket_addr("X").unwrap());
+    assert!(env("127.0.0.1").get_socket_addr("X").is_err());
+}
+
+#[cfg(feature = "url")]
+#[test]
+fn test_get_url() {
+    let url = env("postgres://db:5432/app").get_url("X").unwrap();
+    assert_eq!(Some("db"), url.host_str());
+    assert!(env("not a url").get_url("X").is_err());
+}
+
+#[test]
+fn test_provenance() {
+    let no_file = Provenance::File {
+        file: None,
+        position: Position::new(2, 5),
+    };
+    assert_eq!("defined at 2:5", no_file.to_string());
+    let no_position = Provenance::File {
+        file: Some(PathBuf::from("vars.json")),
+        position: Position::default(),
+    };
+    assert_eq!("defined in vars.json", no_position.to_string());
+    assert_eq!("from the environment", Provenance::Environment.to_string());
+    let secret = Provenance::SecretFile(SecretFile {
+        name: "DB_PASSWORD".to_owned(),
+        variable: "DB_PASSWORD_FILE".to_owned(),
+        path: PathBuf::from("/run/secrets/db"),
+        size: 6,
+    });
+    assert_eq!(
+        "read from \"/run/secrets/db\" ($DB_PASSWORD_FILE)",
+        secret.to_string()
+    );
+}
+
+#[test]
+fn test_apply_secrets() {
+    let project_dir =
+        std::env::var("CARGO_MANIFEST_DIR").expect("Could not determine project directory.");
+    let path = PathBuf::from(project_dir).join("tests/resources/secrets/db_password");
+    let mut env = Env::from_iter([(
+        "DB_PASSWORD_FILE".to_owned(),
+        path.to_string_lossy().into_owned(),
+        Provenance::Environment,
+    )]);
+    env.apply_secrets(&Default::default()).unwrap();
+    let var = env.var("DB_PASSWORD").unwrap();
+    assert_eq!("s3cr3t", var.value);
+    assert!(matches!(&var.provenance, Provenance::SecretFile(secret) if secret.path == path));
+}
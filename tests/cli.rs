@@ -0,0 +1,132 @@
+#![cfg(feature = "cli")]
+
+use std::process::{Command, Output};
+
+use utils::{get_resource_path, AnyRes};
+
+#[allow(dead_code, clippy::into_iter_on_ref)]
+mod utils;
+
+fn potenv(files: &[&str], args: &[&str]) -> AnyRes<Output> {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_potenv"));
+    cmd.env_clear();
+    for file in files {
+        cmd.arg("--file").arg(get_resource_path(file)?);
+    }
+    Ok(cmd.args(args).output()?)
+}
+
+#[test]
+fn test_check() -> AnyRes<()> {
+    let output = potenv(&["dotenv/001.env"], &["check"])?;
+    assert!(output.status.success());
+    let output = potenv(&["dotenv/000-parse-error.env"], &["check"])?;
+    assert_eq!(Some(1), output.status.code());
+    assert!(String::from_utf8(output.stderr)?.contains("line 1, column 6"));
+    Ok(())
+}
+
+#[test]
+fn test_eval() -> AnyRes<()> {
+    let output = potenv(&["dotenv/002a.env", "dotenv/002b.env"], &["eval"])?;
+    assert!(output.status.success());
+    assert_eq!(
+        "FOO='baz'\nBAR='foobar'\n",
+        String::from_utf8(output.stdout)?
+    );
+    Ok(())
+}
+
+#[test]
+fn test_get() -> AnyRes<()> {
+    let output = potenv(&["dotenv/001.env"], &["get", "BAR"])?;
+    assert!(output.status.success());
+    assert_eq!("foobar\n", String::from_utf8(output.stdout)?);
+    let output = potenv(&["dotenv/001.env"], &["get", "NOPE"])?;
+    assert_eq!(Some(1), output.status.code());
+    Ok(())
+}
+
+#[test]
+fn test_export() -> AnyRes<()> {
+    let output = potenv(&["dotenv/001.env"], &["export"])?;
+    assert!(output.status.success());
+    assert_eq!(
+        "export FOO='foo'\nexport BAR='foobar'\n",
+        String::from_utf8(output.stdout)?
+    );
+    Ok(())
+}
+
+#[test]
+fn test_run() -> AnyRes<()> {
+    let output = potenv(
+        &["dotenv/001.env"],
+        &["run", "--", "/bin/sh", "-c", "printf %s \"$BAR\"; exit 42"],
+    )?;
+    assert_eq!(Some(42), output.status.code());
+    assert_eq!("foobar", String::from_utf8(output.stdout)?);
+    let output = potenv(&["dotenv/001.env"], &["run", "--", "/nope"])?;
+    assert_eq!(Some(127), output.status.code());
+    Ok(())
+}
+
+#[test]
+fn test_export_format() -> AnyRes<()> {
+    let output = potenv(&["dotenv/001.env"], &["export", "--format", "docker"])?;
+    assert!(output.status.success());
+    assert_eq!("FOO=foo\nBAR=foobar\n", String::from_utf8(output.stdout)?);
+    let output = potenv(&["dotenv/001.env"], &["export", "--format", "secret"])?;
+    assert!(!output.status.success());
+    Ok(())
+}
+
+#[test]
+fn test_lint() -> AnyRes<()> {
+    let output = potenv(&["dotenv/004-lint.env"], &["lint", "-A", "empty-value"])?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(2, lines.len());
+    assert!(lines[0].ends_with(
+        "004-lint.env:1:1: warning: 'UNDEFINED' is referenced but not defined beforehand [undefined-reference]"
+    ));
+    let output = potenv(
+        &["dotenv/002a.env", "dotenv/002b.env"],
+        &["lint", "--deny", "duplicate-key", "--format", "sarif"],
+    )?;
+    assert_eq!(Some(1), output.status.code());
+    let log: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!("duplicate-key", log["runs"][0]["results"][0]["ruleId"]);
+    assert_eq!("error", log["runs"][0]["results"][0]["level"]);
+    Ok(())
+}
+
+#[test]
+fn test_graph() -> AnyRes<()> {
+    let output = potenv(&["dotenv/004-lint.env"], &["graph"])?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.starts_with("digraph dotenv {\n"));
+    assert!(stdout.contains("\"lower\" -> \"env:UNDEFINED\";\n"));
+    let output = potenv(&["dotenv/004-lint.env"], &["graph", "--inputs"])?;
+    assert_eq!("UNDEFINED\n", String::from_utf8(output.stdout)?);
+    Ok(())
+}
+
+#[test]
+fn test_template() -> AnyRes<()> {
+    let template = get_resource_path("templates/nginx.conf.template")?;
+    let template = template.to_str().unwrap();
+    let output = potenv(
+        &["dotenv/001.env"],
+        &["template", "--keep-unknown", template],
+    )?;
+    assert!(output.status.success());
+    let output = String::from_utf8(output.stdout)?;
+    assert!(output.contains("listen 80;\n"));
+    assert!(output.contains("server_name foo.example.com;\n"));
+    assert!(output.contains("proxy_set_header Host $host;\n"));
+    assert!(output.contains(r#"return 200 '{"bar": "foobar"}';"#));
+    Ok(())
+}
@@ -0,0 +1,65 @@
+#![cfg(unix)]
+
+use std::collections::HashMap;
+
+use potenv::{process::exit_code, Potenv};
+use utils::{get_resource_path, AnyRes};
+
+#[allow(dead_code, clippy::into_iter_on_ref)]
+mod utils;
+
+fn env(vars: &[(&str, &str)]) -> HashMap<String, String> {
+    vars.iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_command_env() -> AnyRes<()> {
+    let file = get_resource_path("dotenv/001.env")?;
+    let potenv = Potenv::new(env(&[("FOO", "env")]), false);
+    let output = potenv
+        .command([&file], "/bin/sh", false)?
+        .args(["-c", "printf '%s %s' \"$FOO\" \"$BAR\""])
+        .output()?;
+    assert_eq!("env envbar", String::from_utf8(output.stdout)?);
+
+    let potenv = Potenv::new(env(&[("FOO", "env")]), true);
+    let output = potenv
+        .command([&file], "/bin/sh", false)?
+        .args(["-c", "printf '%s %s' \"$FOO\" \"$BAR\""])
+        .output()?;
+    assert_eq!("foo foobar", String::from_utf8(output.stdout)?);
+    Ok(())
+}
+
+#[test]
+fn test_command_clear_env() -> AnyRes<()> {
+    let file = get_resource_path("dotenv/001.env")?;
+    let potenv = Potenv::new(env(&[]), false);
+    let output = potenv.command([&file], "/usr/bin/env", true)?.output()?;
+    let mut vars: Vec<_> = String::from_utf8(output.stdout)?
+        .lines()
+        .map(ToOwned::to_owned)
+        .collect();
+    vars.sort();
+    assert_eq!(vec!["BAR=foobar", "FOO=foo"], vars);
+    Ok(())
+}
+
+#[test]
+fn test_exit_code() -> AnyRes<()> {
+    let file = get_resource_path("dotenv/001.env")?;
+    let potenv = Potenv::new(env(&[]), false);
+    let status = potenv
+        .command([&file], "/bin/sh", false)?
+        .args(["-c", "exit 3"])
+        .status()?;
+    assert_eq!(3, exit_code(status));
+    let status = potenv
+        .command([&file], "/bin/sh", false)?
+        .args(["-c", "kill -TERM $$"])
+        .status()?;
+    assert_eq!(128 + 15, exit_code(status));
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use potenv::{
+    dialect::Dialect,
+    evaluator::{EvaluationError, Evaluator},
+    options::Options,
+    parser::{parse_with_options, ParseError},
+};
+use serde::Deserialize;
+use utils::{get_resource_path, AnyRes};
+
+#[allow(dead_code, clippy::into_iter_on_ref)]
+mod utils;
+
+type Scope = HashMap<String, String>;
+
+#[derive(Debug, Deserialize)]
+struct DialectCase {
+    desc: String,
+    input: String,
+    #[serde(default)]
+    env: Scope,
+    #[serde(rename = "override", default)]
+    override_env: bool,
+    #[serde(flatten)]
+    outcome: Outcome,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Outcome {
+    Expected(Scope),
+    Error(String),
+}
+
+fn eval(case: &DialectCase, dialect: Dialect) -> AnyRes<Scope> {
+    let options = Options {
+        dialect,
+        ..Default::default()
+    };
+    let ast = parse_with_options(&case.input, None, options)?;
+    let mut evaluator = Evaluator::new(&case.env, case.override_env);
+    evaluator.evaluate(ast)?;
+    Ok(evaluator.into_scope().into_iter().collect())
+}
+
+fn assert_conformance(dialect: Dialect, path: &str) -> AnyRes<()> {
+    let file = std::fs::File::open(get_resource_path(path)?)?;
+    let cases: Vec<DialectCase> = serde_json::from_reader(file)?;
+    for case in cases {
+        let result = eval(&case, dialect);
+        match &case.outcome {
+            Outcome::Expected(expected) => match result {
+                Ok(scope) => assert_eq!(expected, &scope, "{}", case.desc),
+                Err(err) => panic!("{}: {:?}", case.desc, err),
+            },
+            Outcome::Error(kind) => match (kind.as_str(), result) {
+                ("ParseError", Err(err)) if err.is::<ParseError>() => (),
+                ("EvaluationError", Err(err)) if err.is::<EvaluationError>() => (),
+                (_, result) => panic!("{}: expected {}, got {:?}", case.desc, kind, result),
+            },
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_python_dotenv() -> AnyRes<()> {
+    assert_conformance(Dialect::PythonDotenv, "dialects/python-dotenv.json")
+}
+
+#[test]
+fn test_node() -> AnyRes<()> {
+    assert_conformance(Dialect::Node, "dialects/node.json")
+}
+
+#[test]
+fn test_ruby() -> AnyRes<()> {
+    assert_conformance(Dialect::Ruby, "dialects/ruby.json")
+}
+
+#[test]
+fn test_docker_compose() -> AnyRes<()> {
+    assert_conformance(Dialect::DockerCompose, "dialects/docker-compose.json")
+}
@@ -1,11 +1,21 @@
+#![allow(clippy::redundant_pattern_matching)]
+
 use std::{
     collections::HashMap,
+    net::SocketAddr,
     path::{Path, PathBuf},
+    time::Duration,
 };
 
-use potenv::{Potenv, PotenvError};
+use potenv::{
+    dialect::Dialect,
+    secrets::FileSecrets,
+    vars::{Provenance, VarError},
+    Position, Potenv, PotenvError,
+};
 use utils::{get_resource_path, load_test_cases, AnyRes, ErrorCase, SuccesCase, TestCase};
 
+#[allow(dead_code, clippy::into_iter_on_ref)]
 mod utils;
 
 type Scope = HashMap<String, String>;
@@ -36,19 +46,111 @@ fn test_evaluate() -> AnyRes<()> {
 }
 
 fn assert_success(case: SuccesCase) -> AnyRes<()> {
-    let potenv = Potenv::new(case.env, case.override_env);
+    let potenv = case.options.apply(Potenv::new(case.env, case.override_env));
     let scope = potenv.evaluate(case.files)?;
     assert_eq!(case.expected, scope.collect());
     Ok(())
 }
 
 fn assert_error(case: ErrorCase) -> AnyRes<()> {
-    let potenv = Potenv::new(case.env, case.override_env);
+    let potenv = case.options.apply(Potenv::new(case.env, case.override_env));
     let result = potenv.evaluate(case.files);
     match case.error.as_str() {
         "ParseError" => assert!(matches!(result, Err(PotenvError::ParseError(_)))),
         "EvaluationError" => assert!(matches!(result, Err(PotenvError::EvaluationError(_)))),
+        "SecretFileError" => assert!(matches!(result, Err(PotenvError::SecretFileError(_)))),
         _ => assert!(matches!(result, Err(_))),
     }
     Ok(())
 }
+
+#[test]
+fn test_render() -> AnyRes<()> {
+    let file = get_resource_path("dotenv/001.env")?;
+    let source = std::fs::read_to_string(get_resource_path("templates/nginx.conf.template")?)?;
+    let env = Scope::from([("PORT".to_owned(), "8080".to_owned())]);
+    let potenv = Potenv::new(env, false);
+    let output = potenv.render(vec![&file], &potenv.template(&source)?)?;
+    assert!(output.contains("listen 8080;\n"));
+    assert!(output.contains("proxy_set_header Host ;\n"));
+    let template = potenv.template(&source)?.keep_unknown(true);
+    let output = potenv.render(vec![&file], &template)?;
+    assert!(output.contains("server_name foo.example.com;\n"));
+    assert!(output.contains("proxy_set_header Host $host;\n"));
+    Ok(())
+}
+
+#[test]
+fn test_evaluate_value() -> AnyRes<()> {
+    let file = get_resource_path("dotenv/001.env")?;
+    let env = Scope::from([("FOO".to_owned(), "env".to_owned())]);
+    let scope: potenv::evaluator::Scope = eval(&file, Default::default(), false)?
+        .into_iter()
+        .collect();
+    let value = "\"$BAR ${BAZ:-baz}\"";
+    assert_eq!(
+        "foobar baz",
+        potenv::evaluate_value(value, &scope, &Scope::new())?
+    );
+    assert_eq!("foo", potenv::evaluate_value("$FOO", &scope, &env)?);
+    let potenv = Potenv::new(env.clone(), false);
+    assert_eq!("foo", potenv.evaluate_value("$FOO", &scope)?);
+    let env = Scope::from([("QUX".to_owned(), "env".to_owned())]);
+    assert_eq!("env", potenv::evaluate_value("$QUX", &scope, &env)?);
+    assert_eq!(
+        "env",
+        Potenv::new(env.clone(), false).evaluate_value("$QUX", &scope)?
+    );
+    let potenv = Potenv::new(env, true).filters(true);
+    assert_eq!("FOO", potenv.evaluate_value("${FOO|upper}", &scope)?);
+    let result = potenv.evaluate_value("a b", &scope);
+    assert!(matches!(result, Err(PotenvError::ParseError(_))));
+    let potenv = Potenv::new(Scope::new(), false).dialect(Dialect::Node);
+    assert_eq!("a b", potenv.evaluate_value("'a b'", &scope)?);
+    let template = potenv.template("[$BAR]")?;
+    assert_eq!("[foobar]", template.render(&scope, &Scope::new())?);
+    Ok(())
+}
+
+#[test]
+fn test_typed_env() -> AnyRes<()> {
+    let file = get_resource_path("dotenv/012-typed.env")?;
+    let secrets_dir = get_resource_path("secrets")?;
+    let env = Scope::from([
+        (
+            "SECRETS_DIR".to_owned(),
+            secrets_dir.to_string_lossy().into_owned(),
+        ),
+        ("DEBUG".to_owned(), "off".to_owned()),
+    ]);
+    let potenv = Potenv::new(env, false).file_secrets(Some(FileSecrets::new()));
+    let env = potenv.env(vec![&file])?;
+    assert_eq!(8080, env.get::<u16>("PORT")?);
+    assert!(!env.get_bool("DEBUG")?);
+    assert_eq!(Duration::from_secs(90), env.get_duration("TIMEOUT")?);
+    let hosts: Vec<String> = env.get_list("ALLOWED_HOSTS", ",")?;
+    assert_eq!(vec!["a.example", "b.example"], hosts);
+    assert_eq!(
+        "127.0.0.1:8080".parse::<SocketAddr>()?,
+        env.get_socket_addr("LISTEN")?
+    );
+    let err = env.get::<u8>("WORKERS").unwrap_err();
+    assert!(matches!(
+        env.var("WORKERS").map(|var| &var.provenance),
+        Some(Provenance::File { position, .. }) if *position == Position::new(6, 1)
+    ));
+    let expected = format!(
+        "Invalid value for variable $WORKERS defined at {}:6:1: invalid digit found in string",
+        file.display()
+    );
+    assert_eq!(expected, err.to_string());
+    let err = env.get::<u8>("DEBUG").unwrap_err();
+    assert!(err.to_string().contains("from the environment"));
+    let err = env.get::<u8>("DB_USER").unwrap_err();
+    assert!(matches!(
+        err,
+        VarError::Invalid { provenance, .. }
+            if matches!(*provenance, Provenance::SecretFile(ref secret) if secret.variable == "DB_USER_FILE")
+    ));
+    Ok(())
+}
@@ -0,0 +1,54 @@
+#![cfg(unix)]
+
+use std::process::Command;
+
+use potenv::{
+    evaluator::Scope,
+    export::{shell::Shell, Exporter},
+};
+use utils::AnyRes;
+
+#[allow(dead_code, clippy::into_iter_on_ref)]
+mod utils;
+
+/// Evaluates the exported variables in `sh`, then prints them back NUL-separated.
+fn roundtrip(shell: Shell, vars: &Scope) -> AnyRes<Scope> {
+    let mut script = shell.export(vars)?;
+    for name in vars.keys() {
+        script.push_str(&format!("printf '%s\\0' \"${}\"\n", name));
+    }
+    let output = Command::new("/bin/sh")
+        .arg("-c")
+        .arg(script)
+        .env_clear()
+        .output()?;
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8(output.stdout)?;
+    Ok(vars
+        .keys()
+        .cloned()
+        .zip(stdout.split_terminator('\0').map(ToOwned::to_owned))
+        .collect())
+}
+
+#[test]
+fn test_posix_roundtrip() -> AnyRes<()> {
+    let vars: Scope = [
+        ("EMPTY", ""),
+        ("SPACES", "  a  b  "),
+        ("SINGLE_QUOTES", "it's 'quoted'"),
+        ("DOUBLE_QUOTES", r#"say "hello""#),
+        ("DOLLARS", "$HOME ${PATH} $(pwd) `pwd`"),
+        ("BACKSLASHES", r"\ \\ \n \'"),
+        ("NEWLINES", "a\nb\r\n\n"),
+        ("SPECIAL", "a&b|c;d<e>f(g)*?[h]~#!"),
+        ("UNICODE", "héllo wörld ✓"),
+    ]
+    .into_iter()
+    .map(|(k, v)| (k.to_owned(), v.to_owned()))
+    .collect();
+    for shell in [Shell::Posix, Shell::Bash, Shell::Zsh] {
+        assert_eq!(vars, roundtrip(shell, &vars)?);
+    }
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+
+use potenv::{
+    evaluator::{Evaluator, Scope},
+    export::{systemd::EnvironmentFile, Exporter},
+    import::{docker, systemd},
+    parser::parse,
+};
+use utils::AnyRes;
+
+#[allow(dead_code, clippy::into_iter_on_ref)]
+mod utils;
+
+#[test]
+fn test_mixed_evaluation() -> AnyRes<()> {
+    let env = HashMap::from([("USER".to_owned(), "env".to_owned())]);
+    let mut evaluator = Evaluator::new(&env, false);
+    evaluator.evaluate(docker::parse("HOST=db.local\nPORT=$PORT\nUSER\n")?)?;
+    evaluator.evaluate(systemd::parse("NAME=\"app db\"\n")?)?;
+    evaluator.evaluate(parse("URL=\"${USER}@${HOST}:${PORT}/${NAME}\"", None)?)?;
+    let scope = evaluator.into_scope();
+    assert_eq!("env@db.local:$PORT/app db", scope["URL"]);
+    Ok(())
+}
+
+#[test]
+fn test_systemd_roundtrip() -> AnyRes<()> {
+    let vars: Scope = [
+        ("EMPTY", ""),
+        ("SPACES", "  a  b  "),
+        ("QUOTES", r#"it's "quoted""#),
+        ("SPECIAL", r"$HOME `pwd` \ \\ \n # ;"),
+        ("NEWLINES", "a\nb\n"),
+    ]
+    .into_iter()
+    .map(|(k, v)| (k.to_owned(), v.to_owned()))
+    .collect();
+    let input = EnvironmentFile.export(&vars)?;
+    let env = HashMap::new();
+    let mut evaluator = Evaluator::new(&env, false);
+    evaluator.evaluate(systemd::parse(&input)?)?;
+    assert_eq!(vars, evaluator.into_scope());
+    Ok(())
+}
@@ -1,4 +1,7 @@
+#![allow(clippy::redundant_pattern_matching)]
+
 use std::{collections::HashMap, path::PathBuf};
+#[allow(dead_code, clippy::into_iter_on_ref)]
 mod utils;
 
 use potenv::{Potenv, PotenvError};
@@ -57,7 +60,7 @@ impl From<&ErrorCase> for Setup {
 
 fn assert_success(case: SuccesCase) -> AnyRes<()> {
     let _setup = Setup::from(&case);
-    load(case.files, case.override_env)?;
+    load(case.files, case.override_env, &case.options)?;
     for (k, v) in case.expected {
         let var = std::env::var(k)?;
         assert_eq!(v, var);
@@ -67,19 +70,24 @@ fn assert_success(case: SuccesCase) -> AnyRes<()> {
 
 fn assert_error(case: ErrorCase) -> AnyRes<()> {
     let _setup = Setup::from(&case);
-    let result = load(case.files, case.override_env);
+    let result = load(case.files, case.override_env, &case.options);
     match case.error.as_str() {
         "ParseError" => assert!(matches!(result, Err(PotenvError::ParseError(_)))),
         "EvaluationError" => assert!(matches!(result, Err(PotenvError::EvaluationError(_)))),
+        "SecretFileError" => assert!(matches!(result, Err(PotenvError::SecretFileError(_)))),
         _ => assert!(matches!(result, Err(_))),
     }
     Ok(())
 }
 
-fn load(files: Vec<PathBuf>, override_env: bool) -> Result<HashMap<String, String>, PotenvError> {
-    Ok(if override_env {
-        Potenv::default()
-            .override_env(override_env)
+fn load(
+    files: Vec<PathBuf>,
+    override_env: bool,
+    options: &CaseOptions,
+) -> Result<HashMap<String, String>, PotenvError> {
+    Ok(if override_env || *options != CaseOptions::default() {
+        options
+            .apply(Potenv::default().override_env(override_env))
             .load(files)?
             .collect()
     } else {
@@ -5,6 +5,7 @@ use std::{
     path::{Path, PathBuf},
 };
 
+use potenv::{env::EnvProvider, evaluator::filter::Value, secrets::FileSecrets, Potenv};
 use serde::Deserialize;
 
 pub type AnyRes<T> = Result<T, Box<dyn std::error::Error>>;
@@ -17,6 +18,8 @@ pub struct SuccesCase {
     pub env: HashMap<String, String>,
     #[serde(rename = "override", default)]
     pub override_env: bool,
+    #[serde(default)]
+    pub options: CaseOptions,
     pub expected: HashMap<String, String>,
 }
 
@@ -28,9 +31,54 @@ pub struct ErrorCase {
     pub env: HashMap<String, String>,
     #[serde(rename = "override", default)]
     pub override_env: bool,
+    #[serde(default)]
+    pub options: CaseOptions,
     pub error: String,
 }
 
+/// The options of [Potenv] enabled by a test case, all disabled by default.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct CaseOptions {
+    pub export_prefix: bool,
+    pub string_length: bool,
+    pub pattern_removal: bool,
+    pub bash_expansions: bool,
+    pub tilde_expansion: bool,
+    pub filters: bool,
+    pub resolvers: bool,
+    pub file_secrets: bool,
+    pub max_secret_size: Option<u64>,
+}
+
+impl CaseOptions {
+    /// Enables the options, along with a `slug` filter and a `secret` resolver.
+    pub fn apply<T: EnvProvider>(&self, potenv: Potenv<T>) -> Potenv<T> {
+        let mut file_secrets = self.file_secrets.then(FileSecrets::new);
+        if let Some(max_size) = self.max_secret_size {
+            file_secrets = file_secrets.map(|secrets| secrets.max_size(max_size));
+        }
+        potenv
+            .export_prefix(self.export_prefix)
+            .string_length(self.string_length)
+            .pattern_removal(self.pattern_removal)
+            .bash_expansions(self.bash_expansions)
+            .tilde_expansion(self.tilde_expansion)
+            .filters(self.filters)
+            .filter("slug", |value, _| {
+                Ok(Value::String(
+                    value.into_string().to_lowercase().replace(' ', "-"),
+                ))
+            })
+            .resolvers(self.resolvers)
+            .resolver("secret", |path| match path {
+                "/run/secrets/db" => Ok("s3cr3t".to_owned()),
+                _ => Err(format!("no secret at {path}")),
+            })
+            .file_secrets(file_secrets)
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
 pub enum TestCase {